@@ -0,0 +1,439 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::scene::{
+    description::{
+        AnimationDescription, KeyDescription, Numbers, SceneDescription, TrackDescription,
+    },
+    error::{Location, Problem, Reason, SceneError},
+    json::{index, join},
+    Scene,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Interpolation {
+    Linear,
+    Bezier,
+}
+
+#[derive(Clone, Debug)]
+pub struct Keyframe {
+    pub frame: f32,
+    pub value: Vec<f32>,
+    // how to get from this key to the next one
+    pub interpolation: Interpolation,
+    // the bezier control points either side of the key, worked out from the keys around it when
+    // they are left out so the curve goes through smoothly
+    pub in_handle: Option<Vec<f32>>,
+    pub out_handle: Option<Vec<f32>>,
+}
+
+// one value in the scene file changing over time
+#[derive(Clone, Debug)]
+pub struct Track {
+    // where the value goes, like cameras/front/position. arrays get looked through for something
+    // with that name, or a plain number picks by index
+    pub target: Vec<String>,
+    // in order of frame
+    pub keys: Vec<Keyframe>,
+    // written back as a single number rather than a list
+    scalar: bool,
+}
+
+// the tracks from the animation section of a scene file, and the file they get written over
+#[derive(Clone, Debug)]
+pub struct Animation {
+    // first and last frame, both included
+    pub frames: (u32, u32),
+    pub tracks: Vec<Track>,
+    base: SceneDescription,
+    // the scene file it came from, for error messages
+    file: String,
+}
+
+impl Track {
+    fn new(description: &TrackDescription) -> Track {
+        let interpolation = description.interpolation.unwrap_or(Interpolation::Linear);
+        let mut keys = description
+            .keys
+            .iter()
+            .map(|key| Keyframe {
+                frame: key.frame,
+                value: key.value.to_vec(),
+                interpolation: key.interpolation.unwrap_or(interpolation),
+                in_handle: key.in_handle.as_ref().map(Numbers::to_vec),
+                out_handle: key.out_handle.as_ref().map(Numbers::to_vec),
+            })
+            .collect::<Vec<Keyframe>>();
+        keys.sort_by(|a, b| a.frame.total_cmp(&b.frame));
+        Track {
+            target: description.target.split('/').map(String::from).collect(),
+            scalar: matches!(description.keys[0].value, Numbers::One(_)),
+            keys,
+        }
+    }
+
+    fn describe(&self) -> TrackDescription {
+        let numbers = |values: &Vec<f32>| match self.scalar {
+            true => Numbers::One(values[0]),
+            false => Numbers::Many(values.clone()),
+        };
+        TrackDescription {
+            target: self.target.join("/"),
+            interpolation: None,
+            keys: self
+                .keys
+                .iter()
+                .map(|key| KeyDescription {
+                    frame: key.frame,
+                    value: numbers(&key.value),
+                    interpolation: Some(key.interpolation),
+                    in_handle: key.in_handle.as_ref().map(numbers),
+                    out_handle: key.out_handle.as_ref().map(numbers),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn value_at(&self, frame: f32) -> Vec<f32> {
+        let (first, last) = (&self.keys[0], &self.keys[self.keys.len() - 1]);
+        if frame <= first.frame {
+            return first.value.clone();
+        }
+        if frame >= last.frame {
+            return last.value.clone();
+        }
+        let index = self
+            .keys
+            .windows(2)
+            .position(|pair| frame < pair[1].frame)
+            .unwrap();
+        let (from, to) = (&self.keys[index], &self.keys[index + 1]);
+        let t = (frame - from.frame) / (to.frame - from.frame);
+        match from.interpolation {
+            Interpolation::Linear => from
+                .value
+                .iter()
+                .zip(&to.value)
+                .map(|(a, b)| a + (b - a) * t)
+                .collect(),
+            Interpolation::Bezier => {
+                let out_handle = self.handle(index, 1.0);
+                let in_handle = self.handle(index + 1, -1.0);
+                (0..from.value.len())
+                    .map(|i| {
+                        let s = 1.0 - t;
+                        s.powi(3) * from.value[i]
+                            + 3.0 * s.powi(2) * t * out_handle[i]
+                            + 3.0 * s * t.powi(2) * in_handle[i]
+                            + t.powi(3) * to.value[i]
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    // the control point after the key (side 1) or before it (side -1). without one written down
+    // it follows the slope between the keys either side, and flattens out at the ends so the
+    // motion eases in and out
+    fn handle(&self, index: usize, side: f32) -> Vec<f32> {
+        let key = &self.keys[index];
+        let written = if side > 0.0 {
+            &key.out_handle
+        } else {
+            &key.in_handle
+        };
+        if let Some(handle) = written {
+            return handle.clone();
+        }
+        if index == 0 || index == self.keys.len() - 1 {
+            return key.value.clone();
+        }
+        let (before, after) = (&self.keys[index - 1], &self.keys[index + 1]);
+        let neighbour = if side > 0.0 { after } else { before };
+        let reach = (neighbour.frame - key.frame).abs() / 3.0;
+        key.value
+            .iter()
+            .zip(before.value.iter().zip(&after.value))
+            .map(|(value, (b, a))| value + side * reach * (a - b) / (after.frame - before.frame))
+            .collect()
+    }
+}
+
+impl Animation {
+    // base is the rest of the scene file, which the tracks get written over. anything wrong with
+    // the tracks comes back as problems with the file
+    pub fn new(
+        description: &AnimationDescription,
+        base: &SceneDescription,
+        file_name: &str,
+    ) -> Result<Animation, Vec<Problem>> {
+        let mut problems = vec![];
+        let mut tracks = vec![];
+        for (i, track) in description.tracks.iter().enumerate() {
+            let path = index("animation.tracks", i);
+            let mut problem = |path: String, reason| {
+                problems.push(Problem {
+                    location: Location::Path(path),
+                    reason,
+                })
+            };
+            let Some(first) = track.keys.first() else {
+                problem(
+                    join(&path, "keys"),
+                    Reason::Invalid(String::from("no keys")),
+                );
+                continue;
+            };
+            // every value has to be the same shape as the first so they can be mixed together
+            let length = first.value.to_vec().len();
+            for (j, key) in track.keys.iter().enumerate() {
+                let key_path = index(&join(&path, "keys"), j);
+                for (name, numbers) in [
+                    ("value", Some(&key.value)),
+                    ("in", key.in_handle.as_ref()),
+                    ("out", key.out_handle.as_ref()),
+                ] {
+                    let found = numbers.map(|numbers| numbers.to_vec().len());
+                    if let Some(found) = found.filter(|&found| found != length) {
+                        problem(
+                            join(&key_path, name),
+                            Reason::BadVectorLength {
+                                expected: length,
+                                found,
+                            },
+                        );
+                    }
+                }
+            }
+            tracks.push(Track::new(track));
+        }
+        if let Some([first, last]) = description.frames.filter(|[first, last]| last < first) {
+            problems.push(Problem {
+                location: Location::Path(String::from("animation.frames")),
+                reason: Reason::Invalid(format!("goes backwards from {} to {}", first, last)),
+            });
+        }
+        let last_key = tracks
+            .iter()
+            .flat_map(|track| track.keys.iter().map(|key| key.frame.ceil() as u32))
+            .max()
+            .unwrap_or(0);
+
+        let animation = Animation {
+            frames: description
+                .frames
+                .map(|[first, last]| (first, last))
+                .unwrap_or((0, last_key)),
+            tracks,
+            base: base.clone(),
+            file: file_name.to_string(),
+        };
+        // make sure everything the tracks point at is really there
+        for (i, track) in animation.tracks.iter().enumerate() {
+            let mut base = serde_json::to_value(&animation.base).unwrap();
+            if let Err(reason) = Animation::find_mut(&mut base, &track.target) {
+                problems.push(Problem {
+                    location: Location::Path(index("animation.tracks", i)),
+                    reason,
+                });
+            }
+        }
+        if !problems.is_empty() {
+            return Err(problems);
+        }
+        Ok(animation)
+    }
+
+    // the scene file this animation goes with, as it was before any of the tracks were applied.
+    // saving goes from this rather than the built scene, which can lose things the tracks need
+    // like the axis of a rotation that starts at 0
+    pub fn base(&self) -> &SceneDescription {
+        &self.base
+    }
+
+    // for saving, every key says how it gets to the next one so the track doesnt need to
+    pub fn describe(&self) -> AnimationDescription {
+        AnimationDescription {
+            frames: Some([self.frames.0, self.frames.1]),
+            tracks: self.tracks.iter().map(Track::describe).collect(),
+        }
+    }
+
+    // the scene file as it would be written at this frame
+    pub fn value_at(&self, frame: f32) -> Result<Value, Reason> {
+        let mut scene = serde_json::to_value(&self.base).unwrap();
+        for track in &self.tracks {
+            let value = track.value_at(frame);
+            *Animation::find_mut(&mut scene, &track.target)? = if track.scalar {
+                Value::from(value[0])
+            } else {
+                Value::from(value)
+            };
+        }
+        Ok(scene)
+    }
+
+    pub fn scene_at(&self, frame: u32) -> Result<Scene, SceneError> {
+        let value = self.value_at(frame as f32).map_err(|reason| {
+            SceneError::single(
+                &self.file,
+                Location::Path(String::from("animation")),
+                reason,
+            )
+        })?;
+        // the real name still has to go in, since includes and textures are found next to it
+        Scene::from_value(&value, &self.file).map_err(|mut error| {
+            error.file = format!("{} at frame {}", error.file, frame);
+            error
+        })
+    }
+
+    // follows the path down into the scene file, making empty objects for anything missing so
+    // that a transform can be animated on an item that didnt have one
+    fn find_mut<'a>(mut value: &'a mut Value, path: &[String]) -> Result<&'a mut Value, Reason> {
+        for part in path {
+            value = match value {
+                Value::Array(values) => {
+                    let found = values
+                        .iter()
+                        .position(|v| v["name"].as_str() == Some(part.as_str()))
+                        .or(part.parse::<usize>().ok())
+                        .filter(|&index| index < values.len())
+                        .ok_or_else(|| {
+                            Reason::Invalid(format!("nothing called {} to animate", part))
+                        })?;
+                    &mut values[found]
+                }
+                Value::Null => {
+                    *value = Value::Object(Default::default());
+                    value
+                        .as_object_mut()
+                        .unwrap()
+                        .entry(part.clone())
+                        .or_insert(Value::Null)
+                }
+                Value::Object(fields) => fields.entry(part.clone()).or_insert(Value::Null),
+                _ => return Err(Reason::Invalid(format!("cant animate inside {}", part))),
+            };
+        }
+        Ok(value)
+    }
+}
+
+// where a frame gets saved, a run of #s gets replaced by the frame number padded out to the same
+// length, otherwise the number goes on the end of the name
+pub fn frame_path(pattern: &str, frame: u32) -> String {
+    if let Some(start) = pattern.find('#') {
+        let length = pattern[start..].chars().take_while(|&c| c == '#').count();
+        return format!(
+            "{}{:0length$}{}",
+            &pattern[..start],
+            frame,
+            &pattern[start + length..],
+            length = length
+        );
+    }
+    match pattern.rfind('.') {
+        Some(dot) => format!("{}_{:04}{}", &pattern[..dot], frame, &pattern[dot..]),
+        None => format!("{}_{:04}", pattern, frame),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::frame_path;
+    use crate::scene::{error::Location, Scene};
+
+    #[test]
+    fn tracks_fill_in_between_keys() {
+        let scene = json!({
+            "items": [{ "kind": "sphere", "name": "ball", "origin": [0.0, 0.0, 0.0], "radius": 1.0 }],
+            "animation": { "tracks": [
+                { "target": "items/ball/radius", "keys": [
+                    { "frame": 0, "value": 1.0 },
+                    { "frame": 10, "value": 3.0 },
+                ]},
+                { "target": "items/ball/transform/translate", "interpolation": "bezier", "keys": [
+                    { "frame": 0, "value": [0.0, 0.0, 0.0] },
+                    { "frame": 10, "value": [5.0, 0.0, 0.0] },
+                    { "frame": 20, "value": [10.0, 0.0, 0.0] },
+                ]},
+            ]},
+        });
+        let scene = Scene::from_value(&scene, "test.json").unwrap();
+        let animation = scene.animation.unwrap();
+        assert_eq!(animation.frames, (0, 20));
+
+        let middle = animation.value_at(5.0).unwrap();
+        assert_eq!(middle["items"][0]["radius"], json!(2.0));
+        // straight line through the middle key, eased at the ends
+        let x = |frame| {
+            animation.value_at(frame).unwrap()["items"][0]["transform"]["translate"][0]
+                .as_f64()
+                .unwrap()
+        };
+        assert!((x(10.0) - 5.0).abs() < 1e-5);
+        assert!(x(2.0) < 1.0);
+        assert!((x(12.0) - 6.0).abs() < 0.2);
+        assert_eq!(
+            animation.value_at(30.0).unwrap()["items"][0]["radius"],
+            json!(3.0)
+        );
+    }
+
+    #[test]
+    fn frames_keep_the_real_file_name() {
+        let scene = json!({
+            "items": [{ "kind": "sphere", "name": "ball", "origin": [0.0, 0.0, 0.0], "radius": 1.0 }],
+            "animation": { "tracks": [
+                { "target": "items/ball/radius", "keys": [
+                    { "frame": 0, "value": 1.0 },
+                    { "frame": 10, "value": 3.0 },
+                ]},
+            ]},
+        });
+        let animation = Scene::from_value(&scene, "test.json")
+            .unwrap()
+            .animation
+            .unwrap();
+        assert_eq!(animation.scene_at(5).unwrap().sources[0], "test.json");
+
+        // a scalar written over a vector only breaks once the frame is built
+        let scene = json!({
+            "items": [{ "kind": "sphere", "name": "ball", "origin": [0.0, 0.0, 0.0], "radius": 1.0 }],
+            "animation": { "tracks": [
+                { "target": "items/ball/origin", "keys": [{ "frame": 0, "value": 1.0 }] },
+            ]},
+        });
+        let animation = Scene::from_value(&scene, "test.json")
+            .unwrap()
+            .animation
+            .unwrap();
+        let error = animation.scene_at(3).unwrap_err();
+        assert_eq!(error.file, "test.json at frame 3");
+    }
+
+    #[test]
+    fn backwards_frames_are_a_problem() {
+        let scene = json!({
+            "items": [],
+            "animation": { "frames": [5, 2], "tracks": [] },
+        });
+        let error = Scene::from_value(&scene, "test.json").unwrap_err();
+        assert_eq!(
+            error.problems[0].location,
+            Location::Path(String::from("animation.frames"))
+        );
+    }
+
+    #[test]
+    fn frame_numbers_go_in_the_name() {
+        assert_eq!(frame_path("out/frame_###.png", 7), "out/frame_007.png");
+        assert_eq!(frame_path("out/frame.png", 12), "out/frame_0012.png");
+    }
+}
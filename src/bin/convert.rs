@@ -0,0 +1,76 @@
+// turns scene files of any kind the renderer can load, like the old csv ones, into json scenes
+// that can be edited and have their surfaces changed. run with --help to see the options
+use std::path::Path;
+
+use cgraphics::scene::Scene;
+
+const USAGE: &str = "usage: convert <scene>... [options]
+
+each scene gets written next to itself with a .json extension, so
+  convert many_tris.csv blendertest.csv blender/*.csv
+gives many_tris.json, blendertest.json and a json for each csv in blender
+
+options:
+  -o, --output <path>  where to write the json instead, only when converting one scene
+      --help";
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    if args.is_empty() || args.iter().any(|arg| arg == "--help") {
+        println!("{}", USAGE);
+        return;
+    }
+    if let Err(error) = parse_args(&args).and_then(|jobs| run(&jobs)) {
+        eprintln!("error: {}", error);
+        std::process::exit(1);
+    }
+}
+
+// each scene and where its json goes
+fn parse_args(args: &[String]) -> Result<Vec<(String, String)>, String> {
+    let mut scenes = vec![];
+    let mut output = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" | "--output" => {
+                let path = args.next().ok_or_else(|| format!("{} needs a path", arg))?;
+                output = Some(path.clone());
+            }
+            flag if flag.starts_with('-') => {
+                return Err(format!("dont know what {} is\n\n{}", flag, USAGE))
+            }
+            scene => scenes.push(scene.to_string()),
+        }
+    }
+    match output {
+        Some(output) if scenes.len() == 1 => Ok(vec![(scenes.remove(0), output)]),
+        Some(_) => Err(String::from(
+            "--output only works when converting one scene",
+        )),
+        None => Ok(scenes
+            .into_iter()
+            .map(|scene| {
+                let json = Path::new(&scene).with_extension("json");
+                (scene, json.to_string_lossy().into_owned())
+            })
+            .collect()),
+    }
+}
+
+fn run(jobs: &[(String, String)]) -> Result<(), String> {
+    for (scene, json) in jobs {
+        if scene == json {
+            return Err(format!("{} is already json", scene));
+        }
+        let loaded = Scene::from_file(scene).map_err(|error| error.to_string())?;
+        for warning in &loaded.warnings {
+            eprintln!("warning: {}", warning.describe(scene));
+        }
+        loaded
+            .save_json(json)
+            .map_err(|error| format!("couldnt write {}: {}", json, error))?;
+        println!("{} -> {}", scene, json);
+    }
+    Ok(())
+}
@@ -0,0 +1,351 @@
+// renders a scene straight to an image file without opening a window, for machines with no
+// display. run with --help to see the options
+use std::{
+    io::Write,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use cgraphics::{
+    animation::frame_path,
+    camera::Camera,
+    filter::FilterKind,
+    projection::{self, Projection},
+    sampler::SamplerKind,
+    scene::{description::json_schema, Scene},
+    settings::{IntegratorKind, RenderSettings},
+};
+use image::ImageFormat;
+use nalgebra::Vector3;
+
+const USAGE: &str =
+    "usage: render <scene.json|scene.csv|scene.gltf|scene.glb|scene.obj|scene.ply|scene.pbrt> [options]
+
+options:
+  -o, --output <path>        where to save the picture, render.png if left out
+  -f, --format <format>      png, jpeg, bmp, tga, tiff... worked out from the output name otherwise
+  -w, --width <pixels>
+  -h, --height <pixels>
+  -s, --spp <samples>        samples per pixel
+  -i, --integrator <name>    recursive or normals
+  -c, --camera <name>        one of the cameras in the scene file, the default one otherwise
+  -p, --projection <name>    perspective, orthographic, fisheye or equirectangular
+      --sampler <name>       independent, stratified, halton or sobol
+      --filter <name>        box, tent, gaussian or mitchell
+      --seed <number>
+      --points <radius>      draw the points of a ply point cloud as spheres this big
+      --frames [first..last] render the scene's animation, every frame if no range is given.
+                             #s in the output name get replaced with the frame number
+      --schema               print the json schema for scene files and stop
+      --help";
+
+#[derive(Debug, Default)]
+struct Options {
+    scene: String,
+    output: String,
+    format: Option<ImageFormat>,
+    width: Option<u32>,
+    height: Option<u32>,
+    samples_per_pixel: Option<u32>,
+    integrator: Option<IntegratorKind>,
+    camera: Option<String>,
+    projection: Option<Arc<dyn Projection>>,
+    sampler: Option<SamplerKind>,
+    filter: Option<FilterKind>,
+    seed: Option<u64>,
+    point_radius: Option<f32>,
+    // Some(None) is every frame the animation has
+    frames: Option<Option<(u32, u32)>>,
+}
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    if args.iter().any(|arg| arg == "--help") {
+        println!("{}", USAGE);
+        return;
+    }
+    if args.iter().any(|arg| arg == "--schema") {
+        print!("{}", json_schema());
+        return;
+    }
+    let result = parse_args(&args).and_then(|options| run(&options));
+    if let Err(error) = result {
+        eprintln!("error: {}", error);
+        std::process::exit(1);
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        output: String::from("render.png"),
+        ..Options::default()
+    };
+    let mut scene = None;
+    let mut args = args.iter().peekable();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with('-') {
+            if scene.replace(arg.clone()).is_some() {
+                return Err(format!("only one scene file please\n\n{}", USAGE));
+            }
+            continue;
+        }
+        // --frames is the only one where the value can be left out
+        if arg == "--frames" {
+            options.frames = match args.next_if(|next| !next.starts_with('-')) {
+                Some(range) => Some(Some(parse_range(range)?)),
+                None => Some(None),
+            };
+            continue;
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| format!("{} needs a value\n\n{}", arg, USAGE))?;
+        match arg.as_str() {
+            "-o" | "--output" => options.output = value.clone(),
+            "-f" | "--format" => {
+                options.format = Some(
+                    ImageFormat::from_extension(value)
+                        .ok_or_else(|| format!("unknown image format {}", value))?,
+                )
+            }
+            "-w" | "--width" => options.width = Some(parse_count(arg, value)?),
+            "-h" | "--height" => options.height = Some(parse_count(arg, value)?),
+            "-s" | "--spp" => options.samples_per_pixel = Some(parse_count(arg, value)?),
+            "-i" | "--integrator" => {
+                options.integrator = Some(
+                    IntegratorKind::from_name(value)
+                        .ok_or_else(|| format!("unknown integrator {}", value))?,
+                )
+            }
+            "-c" | "--camera" => options.camera = Some(value.clone()),
+            "-p" | "--projection" => {
+                options.projection = Some(
+                    projection::from_name(value)
+                        .ok_or_else(|| format!("unknown projection {}", value))?,
+                )
+            }
+            "--sampler" => {
+                options.sampler = Some(
+                    SamplerKind::from_name(value)
+                        .ok_or_else(|| format!("unknown sampler {}", value))?,
+                )
+            }
+            "--filter" => {
+                options.filter = Some(
+                    FilterKind::from_name(value)
+                        .ok_or_else(|| format!("unknown filter {}", value))?,
+                )
+            }
+            "--seed" => options.seed = Some(parse_number(arg, value)?),
+            "--points" => options.point_radius = Some(parse_number(arg, value)?),
+            _ => return Err(format!("unknown option {}\n\n{}", arg, USAGE)),
+        }
+    }
+    options.scene = scene.ok_or_else(|| format!("no scene file given\n\n{}", USAGE))?;
+    Ok(options)
+}
+
+fn parse_number<T: std::str::FromStr>(arg: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{} should be a number, not {}", arg, value))
+}
+
+// sizes and sample counts, where 0 would leave nothing to render
+fn parse_count(arg: &str, value: &str) -> Result<u32, String> {
+    match parse_number(arg, value)? {
+        0 => Err(format!("{} should be at least 1\n\n{}", arg, USAGE)),
+        count => Ok(count),
+    }
+}
+
+// 10..20, both ends included
+fn parse_range(range: &str) -> Result<(u32, u32), String> {
+    let (first, last) = range
+        .split_once("..")
+        .ok_or_else(|| format!("frames should look like 0..10, not {}", range))?;
+    let (first, last) = (
+        parse_number("--frames", first)?,
+        parse_number("--frames", last)?,
+    );
+    if last < first {
+        return Err(format!(
+            "frames should go from the first to the last, not {}\n\n{}",
+            range, USAGE
+        ));
+    }
+    Ok((first, last))
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    let started = Instant::now();
+    let scene = match options.point_radius {
+        Some(radius) if options.scene.ends_with(".ply") => {
+            Scene::from_ply(&options.scene, Some(radius))
+        }
+        _ => Scene::from_file(&options.scene),
+    }
+    .map_err(|error| error.to_string())?;
+    for warning in &scene.warnings {
+        eprintln!("warning: {}", warning.describe(&options.scene));
+    }
+    println!(
+        "loaded {} in {:.2}s",
+        options.scene,
+        started.elapsed().as_secs_f32()
+    );
+
+    let format = match options.format {
+        Some(format) => format,
+        None => ImageFormat::from_path(&options.output)
+            .map_err(|_| format!("cant tell what kind of image {} is", options.output))?,
+    };
+
+    let Some(frames) = options.frames else {
+        let samples = render_to_file(&scene, options, &options.output, format, "rendering")?;
+        summary(started, samples, 1);
+        return Ok(());
+    };
+
+    let animation = scene
+        .animation
+        .clone()
+        .ok_or_else(|| format!("{} has no animation in it", options.scene))?;
+    let (first, last) = frames.unwrap_or(animation.frames);
+    let mut samples = 0;
+    for frame in first..=last {
+        let path = frame_path(&options.output, frame);
+        let label = format!("frame {}", frame);
+        let scene = animation
+            .scene_at(frame)
+            .map_err(|error| error.to_string())?;
+        samples += render_to_file(&scene, options, &path, format, &label)?;
+    }
+    summary(started, samples, last.saturating_sub(first) + 1);
+    Ok(())
+}
+
+// renders with a progress bar going and saves it, giving back how many samples it took
+fn render_to_file(
+    scene: &Scene,
+    options: &Options,
+    path: &str,
+    format: ImageFormat,
+    label: &str,
+) -> Result<u64, String> {
+    let (camera, settings) = setup(scene, options)?;
+    let per_pixel = match settings.adaptive {
+        Some(adaptive) => adaptive
+            .max_samples_per_pixel
+            .max(settings.samples_per_pixel),
+        None => settings.samples_per_pixel,
+    };
+    // adaptive sampling usually finishes well before this
+    let most = camera.eyes().len() as u64
+        * camera.width as u64
+        * camera.height as u64
+        * per_pixel.max(1) as u64;
+
+    let samples_taken = AtomicU64::new(0);
+    let finished = AtomicBool::new(false);
+    let started = Instant::now();
+    let buffer = thread::scope(|scope| {
+        scope.spawn(|| {
+            while !finished.load(Ordering::Relaxed) {
+                progress_bar(label, samples_taken.load(Ordering::Relaxed), most);
+                thread::sleep(Duration::from_millis(100));
+            }
+        });
+        let buffer = camera.create_buffer_counting(scene, 1, &settings, &samples_taken);
+        finished.store(true, Ordering::Relaxed);
+        buffer
+    });
+    let samples = samples_taken.load(Ordering::Relaxed);
+    progress_bar(label, most, most);
+    eprintln!();
+
+    Camera::write_image(&buffer, path, format)
+        .map_err(|error| format!("couldnt save {}: {}", path, error))?;
+    println!(
+        "{}x{} at {} spp in {:.2}s, saved to {}",
+        buffer.first().map(|row| row.len()).unwrap_or(0),
+        buffer.len(),
+        settings.samples_per_pixel,
+        started.elapsed().as_secs_f32(),
+        path
+    );
+    Ok(samples)
+}
+
+// the camera and settings the scene asked for, with anything given on the command line on top
+fn setup(scene: &Scene, options: &Options) -> Result<(Camera, RenderSettings), String> {
+    let mut camera = match &options.camera {
+        Some(name) => scene.camera(name).cloned().ok_or_else(|| {
+            let names = scene
+                .cameras
+                .iter()
+                .map(|named| named.name.as_str())
+                .collect::<Vec<&str>>();
+            format!(
+                "no camera called {}, the scene has: {}",
+                name,
+                names.join(", ")
+            )
+        })?,
+        // the same place the viewer starts from
+        None => match scene.default_camera() {
+            Some(camera) => camera.clone(),
+            None => Camera::look_at(
+                Vector3::new(-3.0, 0.0, 1.0),
+                Vector3::new(-2.0, 0.0, 1.0),
+                600,
+                600,
+            ),
+        },
+    };
+    camera.width = options.width.unwrap_or(camera.width);
+    camera.height = options.height.unwrap_or(camera.height);
+    if let Some(projection) = &options.projection {
+        camera.projection = projection.clone();
+    }
+
+    let mut settings = scene.settings;
+    settings.samples_per_pixel = options
+        .samples_per_pixel
+        .unwrap_or(settings.samples_per_pixel);
+    settings.integrator = options.integrator.unwrap_or(settings.integrator);
+    settings.sampler = options.sampler.unwrap_or(settings.sampler);
+    settings.filter = options.filter.unwrap_or(settings.filter);
+    settings.seed = options.seed.unwrap_or(settings.seed);
+    Ok((camera, settings))
+}
+
+fn progress_bar(label: &str, done: u64, total: u64) {
+    const WIDTH: usize = 40;
+    let fraction = (done as f64 / total.max(1) as f64).min(1.0);
+    let filled = (fraction * WIDTH as f64) as usize;
+    eprint!(
+        "\r{} [{}{}] {:3.0}%",
+        label,
+        "#".repeat(filled),
+        "-".repeat(WIDTH - filled),
+        fraction * 100.0
+    );
+    let _ = std::io::stderr().flush();
+}
+
+fn summary(started: Instant, samples: u64, frames: u32) {
+    let seconds = started.elapsed().as_secs_f64();
+    println!(
+        "{} frame{} in {:.2}s total, {} samples at {:.2}M samples/s",
+        frames,
+        if frames == 1 { "" } else { "s" },
+        seconds,
+        samples,
+        samples as f64 / seconds.max(1e-9) / 1e6
+    );
+}
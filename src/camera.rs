@@ -1,139 +1,393 @@
-use eframe::egui::Rgba;
-use image::RgbImage;
-use nalgebra::Vector3;
-use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use std::{
+    f32::consts::PI,
+    fs,
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering as AtomicOrdering},
+        Arc,
+    },
+};
 
-use crate::{renderer::Ray, scene::Scene};
+use epaint::Rgba;
+use image::{
+    error::{ParameterError, ParameterErrorKind},
+    ImageError, ImageFormat, RgbImage,
+};
+use nalgebra::{Rotation3, Unit, Vector3};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 
+use crate::{
+    film::{Film, FilmSample},
+    projection::{perspective::Perspective, Projection},
+    renderer::Ray,
+    sampler::Sampler,
+    scene::Scene,
+    settings::RenderSettings,
+    stereo::Stereo,
+};
+
+// a camera sitting at position and looking at target. up only has to roughly point up, the real
+// up direction gets worked out so that it is square with where the camera is looking
+#[derive(Clone, Debug)]
 pub struct Camera {
-    pub location: Ray,
+    pub position: Vector3<f32>,
+    pub target: Vector3<f32>,
+    pub up: Vector3<f32>,
+    // in degrees, top to bottom of the picture. how wide it is comes from the aspect ratio
+    pub vertical_fov: f32,
+    // size of the lens, 0 is a pinhole and everything is in focus
+    pub aperture_radius: f32,
+    // how far in front of the camera things are perfectly sharp
+    pub focus_distance: f32,
+    // 0 for a round aperture, otherwise the number of sides, which is the shape that out of focus
+    // highlights come out as
+    pub aperture_blades: u32,
+    pub projection: Arc<dyn Projection>,
+    // slants the whole view sideways and up without turning the camera, as a fraction of the
+    // distance forwards. used to line up the two eyes of a stereo camera
+    pub frustum_shift: (f32, f32),
+    pub stereo: Option<Stereo>,
+    // when the shutter opens and closes, anything moving gets smeared between where it is at
+    // those two times. the same open and close is a frozen instant with no motion blur
+    pub shutter: (f32, f32),
     pub width: u32,
     pub height: u32,
 }
 
+// how many pixels get sampled between updates of the sample count
+const COUNTING_CHUNK: usize = 4096;
+
+// how close to straight up or down the camera can look before it stops turning
+const MIN_POLE_ANGLE: f32 = 0.01;
+
 impl Camera {
-    // calls the render function on the provided scene for eah pixel and put it where it should be
-    pub fn create_buffer(&self, scene: &Scene, scaling: u32) -> Vec<Vec<Rgba>> {
-        // init the buffer to pure black
-        let array_of_arrays = (0..self.width / scaling)
-            .into_par_iter()
-            .map(|x| {
-                //let scene_temp_temp = scene_temp.clone();
-                (0..self.height / scaling)
-                    .into_par_iter()
-                    .map(|y| {
-                        // each dimension on screen should be a point from -1 to 1
-                        let x_normalised =
-                            ((-2.0 * x as f32) / (self.width / scaling) as f32) + 1.0;
-                        let y_normalised =
-                            ((2.0 * y as f32) / (self.height / scaling) as f32) - 1.0;
-
-                        let pixel_direction = nalgebra::Vector3::new(
-                            -y_normalised
-                                * self.get_direction_horizontal().sin()
-                                * self.get_direction_vertical().sin(), // who up rotating
-                            y_normalised
-                                * self.get_direction_horizontal().cos()
-                                * self.get_direction_vertical().sin(), // their matrix
-                            x_normalised * self.get_direction_vertical().sin(), // this will need to get an update when the camera can change pitch and it is not defined as the z coordinate
-                        );
-
-                        let pixel_ray = Ray::new(
-                            self.location.origin,
-                            pixel_direction + self.location.direction,
-                        );
-
-                        //println!("{}, {}", x, y);
+    pub fn look_at(
+        position: Vector3<f32>,
+        target: Vector3<f32>,
+        width: u32,
+        height: u32,
+    ) -> Camera {
+        Camera {
+            position,
+            target,
+            up: Vector3::new(0.0, 0.0, 1.0),
+            vertical_fov: 90.0,
+            aperture_radius: 0.0,
+            focus_distance: (target - position).norm(),
+            aperture_blades: 0,
+            projection: Arc::new(Perspective {}),
+            frustum_shift: (0.0, 0.0),
+            stereo: None,
+            shutter: (0.0, 0.0),
+            width,
+            height,
+        }
+    }
+
+    // forward, right and up, all at right angles to each other and of length 1
+    pub fn basis(&self) -> (Vector3<f32>, Vector3<f32>, Vector3<f32>) {
+        let forward = (self.target - self.position).normalize();
+        let mut right = forward.cross(&self.up);
+        if right.norm() < 1e-6 {
+            // looking straight along up, any right will do as long as its consistent
+            right = forward.cross(&Vector3::new(1.0, 0.0, 0.0));
+            if right.norm() < 1e-6 {
+                right = forward.cross(&Vector3::new(0.0, 1.0, 0.0));
+            }
+        }
+        let right = right.normalize();
+        let up = right.cross(&forward);
+        (forward, right, up)
+    }
+
+    // leans a direction over by the frustum shift for every bit it goes forwards, the same slant
+    // the perspective projection gets from moving its picture sideways
+    pub fn shifted(&self, direction: Vector3<f32>) -> Vector3<f32> {
+        let (forward, right, up) = self.basis();
+        let (shift_x, shift_y) = self.frustum_shift;
+        direction + (right * shift_x + up * shift_y) * direction.dot(&forward)
+    }
+
+    // renders the scene and filters the samples into where they should be
+    pub fn create_buffer(
+        &self,
+        scene: &Scene,
+        scaling: u32,
+        settings: &RenderSettings,
+    ) -> Vec<Vec<Rgba>> {
+        self.create_buffer_counting(scene, scaling, settings, &AtomicU64::new(0))
+    }
+
+    // the same as create_buffer, but keeps adding up how many samples have been taken so that
+    // another thread can show how far along it is
+    pub fn create_buffer_counting(
+        &self,
+        scene: &Scene,
+        scaling: u32,
+        settings: &RenderSettings,
+        samples_taken: &AtomicU64,
+    ) -> Vec<Vec<Rgba>> {
+        let buffers = self
+            .eyes()
+            .iter()
+            .map(|eye| {
+                eye.create_film_counting(scene, scaling, settings, samples_taken)
+                    .resolve()
+            })
+            .collect();
+        self.combine_eyes(buffers)
+    }
+
+    // the left and right eye if this is a stereo camera, otherwise just the camera itself
+    pub fn eyes(&self) -> Vec<Camera> {
+        match &self.stereo {
+            Some(stereo) => vec![stereo.eye(self, -1.0), stereo.eye(self, 1.0)],
+            None => vec![self.clone()],
+        }
+    }
+
+    // puts the pictures from each of the eyes back together
+    pub fn combine_eyes(&self, mut buffers: Vec<Vec<Vec<Rgba>>>) -> Vec<Vec<Rgba>> {
+        match (&self.stereo, buffers.len()) {
+            (Some(stereo), 2) => {
+                let right = buffers.pop().unwrap();
+                stereo.combine(buffers.pop().unwrap(), right)
+            }
+            _ => buffers.pop().unwrap_or_default(),
+        }
+    }
+
+    // calls the render function on the provided scene for each sample in each pixel. with
+    // adaptive sampling the noisy pixels keep getting more samples until they are clean enough
+    pub fn create_film(&self, scene: &Scene, scaling: u32, settings: &RenderSettings) -> Film {
+        self.create_film_counting(scene, scaling, settings, &AtomicU64::new(0))
+    }
+
+    pub fn create_film_counting(
+        &self,
+        scene: &Scene,
+        scaling: u32,
+        settings: &RenderSettings,
+        samples_taken: &AtomicU64,
+    ) -> Film {
+        let width = self.width / scaling;
+        let height = self.height / scaling;
+        let batch = settings.samples_per_pixel.max(1);
+        let max_samples = match settings.adaptive {
+            Some(adaptive) => adaptive.max_samples_per_pixel.max(batch),
+            None => batch,
+        };
+        // the sampler has to know about every sample a pixel could end up with
+        let sampler = settings.sampler.build(max_samples, settings.seed);
+
+        // a chunk at a time so the count goes up while it works rather than all at the end
+        let sample = |film: &mut Film, pixels: &[(u32, u32)]| {
+            for chunk in pixels.chunks(COUNTING_CHUNK) {
+                let count = |film: &Film| {
+                    chunk
+                        .iter()
+                        .map(|&(x, y)| film.sample_count(x, y) as u64)
+                        .sum::<u64>()
+                };
+                let before = count(film);
+                self.sample_pixels(scene, film, chunk, batch, sampler.as_ref(), settings);
+                samples_taken.fetch_add(count(film) - before, AtomicOrdering::Relaxed);
+            }
+        };
+
+        let mut film = Film::new(width, height, settings.filter.build());
+        let everything = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .collect::<Vec<(u32, u32)>>();
+        sample(&mut film, &everything);
+
+        if let Some(adaptive) = settings.adaptive {
+            loop {
+                let noisy = film.noisy_pixels(adaptive.noise_threshold, max_samples);
+                if noisy.is_empty() {
+                    break;
+                }
+                sample(&mut film, &noisy);
+            }
+        }
+        film
+    }
+
+    // adds up to count more samples to each of the pixels, without going past what the sampler
+    // was set up for
+    fn sample_pixels(
+        &self,
+        scene: &Scene,
+        film: &mut Film,
+        pixels: &[(u32, u32)],
+        count: u32,
+        sampler: &dyn Sampler,
+        settings: &RenderSettings,
+    ) {
+        let (width, height) = film.size();
+        let new_samples = pixels
+            .par_iter()
+            .map(|&(x, y)| {
+                // each pixel gets its own sampler so the threads can share
+                let mut sampler = sampler.clone_box();
+                let first = film.sample_count(x, y);
+                let last = (first + count).min(sampler.samples_per_pixel());
+                let samples = (first..last)
+                    .map(|sample_index| {
+                        sampler.start_pixel_sample((x, y), sample_index);
+                        // jitter inside the pixel so that edges get smoothed out
+                        let (jitter_x, jitter_y) = sampler.get_2d();
+                        let lens = sampler.get_2d();
+                        let (open, close) = self.shutter;
+                        let time = open + (close - open) * sampler.get_1d();
+                        let pixel_ray = self
+                            .pixel_ray(
+                                x as f32 + jitter_x,
+                                y as f32 + jitter_y,
+                                width,
+                                height,
+                                lens,
+                            )
+                            .map(|ray| ray.at_time(time));
+
                         // do the calculations and put it in the buffer
-                        let color = scene.test_intersections(pixel_ray, 0).0.colour;
-                        //let color = scene_temp_temp.test_intersections_vec(pixel_ray).colour;
-                        return color;
+                        let colour = match pixel_ray {
+                            Some(ray) => scene.integrate(ray, settings, sampler.as_mut()),
+                            None => Rgba::BLACK,
+                        };
+                        FilmSample {
+                            offset: (jitter_x - 0.5, jitter_y - 0.5),
+                            colour,
+                        }
                     })
-                    .collect::<Vec<Rgba>>()
+                    .collect::<Vec<FilmSample>>();
+                (x, y, samples)
             })
-            .collect();
-        array_of_arrays
+            .collect::<Vec<(u32, u32, Vec<FilmSample>)>>();
+        for (x, y, samples) in new_samples {
+            film.add_samples(x, y, samples);
+        }
     }
 
-    #[allow(dead_code)]
-    pub fn save_to_file(framebuffer: &Vec<Vec<Rgba>>, name: Option<&str>) {
-        let rgba_to_bytes = |pixel: Rgba| {
-            println!("{}, {}, {}", pixel.r(), pixel.g(), pixel.b());
-            let ret = vec![
-                (pixel.r() * 255.0) as u8,
-                (pixel.g() * 255.0) as u8,
-                (pixel.b() * 255.0) as u8,
-            ];
-            println!("{:?}", ret);
-            return ret;
+    // the ray going through a point on the screen, measured in pixels from the top left. lens
+    // picks where on the aperture the ray starts from, (0.5, 0.5) is the middle. None if the
+    // projection cant see that far out
+    pub fn pixel_ray(
+        &self,
+        x: f32,
+        y: f32,
+        width: u32,
+        height: u32,
+        lens: (f32, f32),
+    ) -> Option<Ray> {
+        // each dimension on screen should be a point from -1 to 1, with y going up
+        let x_normalised = (2.0 * x / width as f32) - 1.0;
+        let y_normalised = 1.0 - (2.0 * y / height as f32);
+        let aspect = width as f32 / height as f32;
+        self.projection
+            .generate_ray(self, x_normalised, y_normalised, aspect, lens)
+    }
+
+    // sets the focus distance to whatever is under the given pixel, returns false if there was
+    // nothing there to focus on
+    pub fn focus_on(&mut self, scene: &Scene, x: f32, y: f32) -> bool {
+        let Some(ray) = self.pixel_ray(x, y, self.width, self.height, (0.5, 0.5)) else {
+            return false;
+        };
+        if let Some(distance) = scene.closest_hit(&ray).0.distance {
+            if distance < f32::MAX {
+                let (forward, _, _) = self.basis();
+                // the focus plane is flat, so only the forwards part of the distance counts
+                self.focus_distance = distance * ray.direction.dot(&forward);
+                return true;
+            }
+        }
+        return false;
+    }
+
+    // saves the picture the same way it looks in the window. an empty picture, or rows that
+    // arent all the same length, cant be saved
+    pub fn write_image(
+        framebuffer: &[Vec<Rgba>],
+        path: &str,
+        format: ImageFormat,
+    ) -> image::ImageResult<()> {
+        let mismatch = || {
+            ImageError::Parameter(ParameterError::from_kind(
+                ParameterErrorKind::DimensionMismatch,
+            ))
         };
+        let width = framebuffer.first().map_or(0, Vec::len);
+        if width == 0 || framebuffer.iter().any(|row| row.len() != width) {
+            return Err(mismatch());
+        }
         let image = RgbImage::from_vec(
+            width as u32,
             framebuffer.len() as u32,
-            framebuffer[0].len() as u32,
             framebuffer
-                .clone()
-                .into_iter()
-                .flatten()
-                .map(|pixel: Rgba| rgba_to_bytes(pixel))
+                .iter()
                 .flatten()
+                .flat_map(|pixel: &Rgba| {
+                    let [r, g, b, _] = pixel.to_srgba_unmultiplied();
+                    [r, g, b]
+                })
                 .collect(),
         )
-        .unwrap();
+        .ok_or_else(mismatch)?;
+        if let Some(parent) = Path::new(path).parent() {
+            fs::create_dir_all(parent)?;
+        }
+        image.save_with_format(path, format)
+    }
+
+    #[allow(dead_code)]
+    pub fn save_to_file(framebuffer: &[Vec<Rgba>], name: Option<&str>) {
         let time = chrono::Local::now();
         let filename = if let Some(descriptor) = name {
             format!("{} {}", time.to_rfc3339(), descriptor)
         } else {
             time.to_rfc3339()
         };
-        image
-            .save_with_format(
-                format!("./images/{}.png", filename),
-                image::ImageFormat::Png,
-            )
-            .unwrap();
-    }
-
-    pub fn rotate(&mut self, dtheta: f32, dphi: f32) {
-        let r = self.location.direction.norm();
-
-        let phi_1 = self.get_direction_vertical() - dphi;
-        let theta_1 = self.get_direction_horizontal() - dtheta;
-
-        let z_1 = phi_1.cos() * r;
-        let x_1 = theta_1.cos() * r * phi_1.sin();
-        let y_1 = theta_1.sin() * r * phi_1.sin();
-
-        self.location.direction.x = x_1;
-        self.location.direction.y = y_1;
-        self.location.direction.z = z_1;
-        println!(
-            "{:?}, {}",
-            self.location.direction,
-            self.get_direction_vertical()
-        );
+        Camera::write_image(
+            framebuffer,
+            &format!("./images/{}.png", filename),
+            ImageFormat::Png,
+        )
+        .unwrap();
     }
 
-    fn get_direction_horizontal(&self) -> f32 {
-        let x = self.location.direction.x;
-        let y = self.location.direction.y;
+    // turns the camera on the spot, yaw goes around up (positive turns left) and pitch tilts it
+    // (positive looks up). pitch stops just short of straight up or down so it never flips over
+    pub fn rotate(&mut self, yaw: f32, pitch: f32) {
+        let distance = (self.target - self.position).norm();
+        let (forward, right, _) = self.basis();
+        let world_up = self.up.normalize();
 
-        // trust me this is right
-        y.atan2(x)
-    }
+        let turn = Rotation3::from_axis_angle(&Unit::new_normalize(world_up), yaw);
+        let yawed = turn * forward;
+        let right = turn * right;
 
-    fn get_direction_vertical(&self) -> f32 {
-        let mag_horizontal =
-            (self.location.direction.x.powi(2) + self.location.direction.y.powi(2)).sqrt();
-        mag_horizontal.atan2(self.location.direction.z)
+        // pitch is measured as the angle down from straight up
+        let current = forward.dot(&world_up).clamp(-1.0, 1.0).acos();
+        let limited = (current - pitch).clamp(MIN_POLE_ANGLE, PI - MIN_POLE_ANGLE);
+        let pitched =
+            Rotation3::from_axis_angle(&Unit::new_normalize(right), current - limited) * yawed;
+
+        self.target = self.position + pitched.normalize() * distance;
     }
 
+    // moves relative to where the camera is facing, x is forwards along the ground, y is to the
+    // right and z is straight up. the target comes along too
     pub fn move_by(&mut self, direction: Vector3<f32>) {
-        // rawdogging the rotation matricies (sorry future boss)
-        self.location.origin.x += direction.x * self.get_direction_horizontal().cos()
-            - direction.y * self.get_direction_horizontal().sin();
-        self.location.origin.y += direction.x * self.get_direction_horizontal().sin()
-            + direction.y * self.get_direction_horizontal().cos();
-        self.location.origin.z += direction.z;
+        let (forward, right, _) = self.basis();
+        let world_up = self.up.normalize();
+        let flat_forward = (forward - world_up * forward.dot(&world_up))
+            .try_normalize(1e-6)
+            .unwrap_or(world_up.cross(&right));
+
+        let offset = flat_forward * direction.x + right * direction.y + world_up * direction.z;
+        self.position += offset;
+        self.target += offset;
     }
 
     /*
@@ -187,3 +441,125 @@ impl Camera {
         buffer
     }*/
 }
+
+#[cfg(test)]
+mod test {
+    use epaint::Rgba;
+    use image::ImageFormat;
+
+    use crate::{
+        camera::Camera,
+        filter::FilterKind,
+        sampler::SamplerKind,
+        scene::Scene,
+        settings::{AdaptiveSettings, RenderSettings},
+    };
+
+    #[test]
+    fn same_seed_same_image() {
+        let camera = Camera::look_at(
+            nalgebra::Vector3::new(-3.0, 0.0, 1.0),
+            nalgebra::Vector3::new(-2.0, 0.0, 1.0),
+            24,
+            16,
+        );
+        let scene = Scene::from_json("jsons/ci.json").unwrap();
+
+        // the thread count changes which pixel gets rendered when, it shouldnt change the result
+        let render = |threads: usize, seed: u64| {
+            let settings = RenderSettings {
+                sampler: SamplerKind::Independent,
+                samples_per_pixel: 2,
+                seed,
+                filter: FilterKind::Gaussian,
+                adaptive: Some(AdaptiveSettings {
+                    noise_threshold: 0.1,
+                    max_samples_per_pixel: 8,
+                }),
+                ..RenderSettings::default()
+            };
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap()
+                .install(|| camera.create_buffer(&scene, 1, &settings))
+        };
+        let to_bits = |buffer: Vec<Vec<epaint::Rgba>>| {
+            buffer
+                .into_iter()
+                .flatten()
+                .map(|pixel| pixel.to_array().map(f32::to_bits))
+                .collect::<Vec<[u32; 4]>>()
+        };
+
+        assert_eq!(to_bits(render(1, 7)), to_bits(render(4, 7)));
+        assert_ne!(to_bits(render(1, 7)), to_bits(render(1, 8)));
+    }
+
+    #[test]
+    fn pitch_stops_at_the_poles() {
+        let mut camera = Camera::look_at(
+            nalgebra::Vector3::new(0.0, 0.0, 0.0),
+            nalgebra::Vector3::new(1.0, 0.0, 0.0),
+            30,
+            10,
+        );
+        for _ in 0..100 {
+            camera.rotate(0.0, 0.1);
+        }
+        let (forward, right, up) = camera.basis();
+        // still looking the same way around, just nearly straight up
+        assert!(forward.z > 0.99 && forward.x > 0.0);
+        assert!((right - nalgebra::Vector3::new(0.0, -1.0, 0.0)).norm() < 1e-3);
+        assert!(up.dot(&forward).abs() < 1e-5 && up.dot(&right).abs() < 1e-5);
+
+        // a wide picture should see further to the sides than up and down
+        let camera = Camera::look_at(
+            nalgebra::Vector3::new(0.0, 0.0, 0.0),
+            nalgebra::Vector3::new(1.0, 0.0, 0.0),
+            30,
+            10,
+        );
+        let centre = camera.pixel_ray(15.0, 5.0, 30, 10, (0.5, 0.5)).unwrap();
+        let side = camera.pixel_ray(30.0, 5.0, 30, 10, (0.5, 0.5)).unwrap();
+        let top = camera.pixel_ray(15.0, 0.0, 30, 10, (0.5, 0.5)).unwrap();
+        assert!((centre.direction - nalgebra::Vector3::new(1.0, 0.0, 0.0)).norm() < 1e-6);
+        assert!((top.direction.z / top.direction.x - 1.0).abs() < 1e-5);
+        assert!((-side.direction.y / side.direction.x - 3.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn lens_rays_meet_on_the_focus_plane() {
+        let mut camera = Camera::look_at(
+            nalgebra::Vector3::new(0.0, 0.0, 0.0),
+            nalgebra::Vector3::new(1.0, 0.0, 0.0),
+            20,
+            20,
+        );
+        camera.aperture_radius = 0.5;
+        camera.focus_distance = 4.0;
+        for blades in [0, 6] {
+            camera.aperture_blades = blades;
+            let meeting_points = [(0.1, 0.2), (0.9, 0.5), (0.4, 0.99)].map(|lens| {
+                let ray = camera.pixel_ray(3.0, 12.0, 20, 20, lens).unwrap();
+                // walk forwards to where x is the focus distance
+                ray.at_point((4.0 - ray.origin.x) / ray.direction.x)
+            });
+            assert!((meeting_points[0] - meeting_points[1]).norm() < 1e-4);
+            assert!((meeting_points[0] - meeting_points[2]).norm() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn pictures_with_nothing_in_them_arent_saved() {
+        let grey = Rgba::from_gray(0.5);
+        for buffer in [
+            vec![],
+            vec![vec![]],
+            vec![vec![grey; 2], vec![grey], vec![grey; 3]],
+        ] {
+            let saved = Camera::write_image(&buffer, "never_written.png", ImageFormat::Png);
+            assert!(saved.is_err(), "{:?}", buffer);
+        }
+    }
+}
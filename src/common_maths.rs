@@ -1 +1,2 @@
 pub mod maths;
+pub mod transform;
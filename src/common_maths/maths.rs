@@ -1,3 +1,5 @@
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
 use nalgebra::Vector3;
 
 use crate::renderer::Ray;
@@ -13,3 +15,37 @@ pub fn reflected_ray(normal: &Ray, incoming: &Ray) -> Ray {
     let reflected_ray = Ray::new_preserve(normal.origin, reflected_direction);
     reflected_ray
 }
+
+// spreads a point from the unit square evenly over a disk of radius 1, keeping nearby points near
+// each other (shirley and chiu's concentric mapping)
+pub fn sample_disk(u: f32, v: f32) -> (f32, f32) {
+    let (a, b) = (2.0 * u - 1.0, 2.0 * v - 1.0);
+    if a == 0.0 && b == 0.0 {
+        return (0.0, 0.0);
+    }
+    let (r, theta) = if a.abs() > b.abs() {
+        (a, FRAC_PI_4 * (b / a))
+    } else {
+        (b, FRAC_PI_2 - FRAC_PI_4 * (a / b))
+    };
+    (r * theta.cos(), r * theta.sin())
+}
+
+// spreads a point from the unit square evenly over a regular polygon with its corners on the unit
+// circle, used for the blades of a camera aperture
+pub fn sample_polygon(u: f32, v: f32, sides: u32) -> (f32, f32) {
+    // pick which triangle out of the middle, then reuse the rest of u inside it
+    let scaled = u * sides as f32;
+    let side = (scaled as u32).min(sides - 1);
+    let u = scaled - side as f32;
+
+    let angle = 2.0 * PI / sides as f32;
+    let (a, b) = (angle * side as f32, angle * (side + 1) as f32);
+    // uniform point in the triangle between the centre and the two corners
+    let r = u.sqrt();
+    let (weight_a, weight_b) = (r * (1.0 - v), r * v);
+    (
+        weight_a * a.cos() + weight_b * b.cos(),
+        weight_a * a.sin() + weight_b * b.sin(),
+    )
+}
@@ -0,0 +1,39 @@
+use nalgebra::{Similarity3, Translation3, UnitQuaternion, Vector3};
+
+// moves, turns and evenly scales something, applied in the order scale, rotate, translate
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    pub translation: Vector3<f32>,
+    pub rotation: UnitQuaternion<f32>,
+    pub scale: f32,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform {
+            translation: Vector3::zeros(),
+            rotation: UnitQuaternion::identity(),
+            scale: 1.0,
+        }
+    }
+}
+
+impl Transform {
+    // in between two transforms, t of 0 is this one and 1 is the other. the rotation goes the
+    // short way round at a steady speed
+    pub fn lerp(&self, other: &Transform, t: f32) -> Transform {
+        Transform {
+            translation: self.translation.lerp(&other.translation, t),
+            rotation: self.rotation.slerp(&other.rotation, t),
+            scale: self.scale + (other.scale - self.scale) * t,
+        }
+    }
+
+    pub fn to_similarity(self) -> Similarity3<f32> {
+        Similarity3::from_parts(
+            Translation3::from(self.translation),
+            self.rotation,
+            self.scale,
+        )
+    }
+}
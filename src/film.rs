@@ -0,0 +1,221 @@
+use std::sync::Arc;
+
+use epaint::Rgba;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::filter::Filter;
+
+#[derive(Clone, Copy, Debug)]
+pub struct FilmSample {
+    // where the sample landed relative to the centre of its pixel, each between -0.5 and 0.5
+    pub offset: (f32, f32),
+    pub colour: Rgba,
+}
+
+// the samples filtered into the pixels they reach as they get added, so the film stays the same
+// size however many samples it takes. indexed the same way as the buffer, a row at a time from
+// the top
+#[derive(Clone, Debug)]
+pub struct Film {
+    filter: Arc<dyn Filter>,
+    pixels: Vec<Vec<FilmPixel>>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct FilmPixel {
+    // every sample that reached the pixel times how much the filter says it counts
+    colour: Rgba,
+    weight: f32,
+    // how many samples were taken inside the pixel, and the running mean and sum of squared
+    // differences of their brightness for working out how noisy it is
+    count: u32,
+    mean: f32,
+    m2: f32,
+}
+
+impl Film {
+    pub fn new(width: u32, height: u32, filter: Arc<dyn Filter>) -> Film {
+        Film {
+            filter,
+            pixels: vec![vec![FilmPixel::default(); width as usize]; height as usize],
+        }
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        let width = self.pixels.first().map(|row| row.len()).unwrap_or(0);
+        (width as u32, self.pixels.len() as u32)
+    }
+
+    pub fn sample_count(&self, x: u32, y: u32) -> u32 {
+        self.pixels[y as usize][x as usize].count
+    }
+
+    // splats each sample into every pixel close enough for the filter to reach
+    pub fn add_samples(&mut self, x: u32, y: u32, samples: Vec<FilmSample>) {
+        let (width, height) = self.size();
+        let (width, height) = (width as i64, height as i64);
+        let (x, y) = (x as i64, y as i64);
+        let reach = (self.filter.radius() + 0.5).ceil() as i64;
+        for sample in samples {
+            for ny in (y - reach).max(0)..(y + reach + 1).min(height) {
+                for nx in (x - reach).max(0)..(x + reach + 1).min(width) {
+                    let dx = (x - nx) as f32 + sample.offset.0;
+                    let dy = (y - ny) as f32 + sample.offset.1;
+                    let weight = self.filter.evaluate(dx, dy);
+                    if weight == 0.0 {
+                        continue;
+                    }
+                    let pixel = &mut self.pixels[ny as usize][nx as usize];
+                    pixel.colour = pixel.colour + sample.colour.multiply(weight);
+                    pixel.weight += weight;
+                }
+            }
+            // welford's method, so the spread can be kept up to date one sample at a time
+            let pixel = &mut self.pixels[y as usize][x as usize];
+            let luminance = luminance(sample.colour);
+            pixel.count += 1;
+            let delta = luminance - pixel.mean;
+            pixel.mean += delta / pixel.count as f32;
+            pixel.m2 += delta * (luminance - pixel.mean);
+        }
+    }
+
+    // how unsure we are about the brightness of a pixel, the standard error of the mean divided
+    // by the mean so that dark and bright pixels get treated the same
+    pub fn relative_error(&self, x: u32, y: u32) -> f32 {
+        let pixel = &self.pixels[y as usize][x as usize];
+        if pixel.count < 2 {
+            return f32::INFINITY;
+        }
+        let n = pixel.count as f32;
+        let variance = pixel.m2 / (n - 1.0);
+        // the small constant stops black pixels from never being good enough
+        (variance / n).sqrt() / (pixel.mean + 1e-3)
+    }
+
+    // the pixels that are still noisier than the threshold and allowed to have more samples
+    pub fn noisy_pixels(&self, threshold: f32, max_samples: u32) -> Vec<(u32, u32)> {
+        let (width, height) = self.size();
+        (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .filter(|&(x, y)| {
+                self.sample_count(x, y) < max_samples && self.relative_error(x, y) > threshold
+            })
+            .collect()
+    }
+
+    // how many samples went into each pixel, blue for the fewest up to red and then yellow for
+    // the most
+    pub fn sample_heatmap(&self) -> Vec<Vec<Rgba>> {
+        let most = self
+            .pixels
+            .iter()
+            .flatten()
+            .map(|pixel| pixel.count)
+            .max()
+            .unwrap_or(0)
+            .max(1) as f32;
+        self.pixels
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|pixel| {
+                        let t = pixel.count as f32 / most;
+                        if t < 0.5 {
+                            Rgba::from_rgb(2.0 * t, 0.0, 1.0 - 2.0 * t)
+                        } else {
+                            Rgba::from_rgb(1.0, 2.0 * t - 1.0, 0.0)
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    // each pixel is the weighted average of all the samples close enough for the filter to reach
+    pub fn resolve(&self) -> Vec<Vec<Rgba>> {
+        self.pixels
+            .par_iter()
+            .map(|row| {
+                row.iter()
+                    .map(|pixel| {
+                        if pixel.weight <= 0.0 {
+                            return Rgba::BLACK;
+                        }
+                        // the negative bits of some filters can push a colour below zero
+                        let [r, g, b, a] = pixel.colour.multiply(1.0 / pixel.weight).to_array();
+                        Rgba::from_rgba_premultiplied(r.max(0.0), g.max(0.0), b.max(0.0), a)
+                    })
+                    .collect::<Vec<Rgba>>()
+            })
+            .collect()
+    }
+}
+
+fn luminance(colour: Rgba) -> f32 {
+    0.2126 * colour.r() + 0.7152 * colour.g() + 0.0722 * colour.b()
+}
+
+#[cfg(test)]
+mod test {
+    use epaint::Rgba;
+
+    use crate::{
+        film::{Film, FilmSample},
+        filter::FilterKind,
+    };
+
+    #[test]
+    fn flat_colour_stays_flat() {
+        let sample = |offset| FilmSample {
+            offset,
+            colour: Rgba::from_rgb(0.25, 0.5, 1.0),
+        };
+        let pixel = vec![
+            sample((-0.3, 0.1)),
+            sample((0.4, -0.2)),
+            sample((0.0, 0.45)),
+        ];
+        for kind in FilterKind::ALL {
+            let mut film = Film::new(6, 5, kind.build());
+            for x in 0..6 {
+                for y in 0..5 {
+                    film.add_samples(x, y, pixel.clone());
+                }
+            }
+            // they are all the same so there is no noise to get rid of
+            assert!(film.noisy_pixels(0.01, 100).is_empty());
+
+            for pixel in film.resolve().into_iter().flatten() {
+                let difference = pixel.to_array()[..3]
+                    .iter()
+                    .zip([0.25, 0.5, 1.0])
+                    .map(|(a, b)| (a - b).abs())
+                    .fold(0.0, f32::max);
+                assert!(difference < 1e-5, "{:?} gave {:?}", kind, pixel);
+            }
+        }
+    }
+
+    #[test]
+    fn noisy_pixels_get_found() {
+        let sample = |brightness| FilmSample {
+            offset: (0.0, 0.0),
+            colour: Rgba::from_rgb(brightness, brightness, brightness),
+        };
+        let mut film = Film::new(2, 1, FilterKind::Box.build());
+        film.add_samples(0, 0, vec![sample(0.5); 4]);
+        film.add_samples(
+            1,
+            0,
+            vec![sample(0.0), sample(1.0), sample(0.0), sample(1.0)],
+        );
+        assert_eq!(film.sample_count(1, 0), 4);
+        // a mean of 0.5 and a standard deviation of about 0.577, over the square root of 4
+        let expected = (1.0f32 / 3.0 / 4.0).sqrt() / (0.5 + 1e-3);
+        assert!((film.relative_error(1, 0) - expected).abs() < 1e-4);
+        assert_eq!(film.noisy_pixels(0.1, 100), vec![(1, 0)]);
+        // unless it has had all the samples it is allowed
+        assert!(film.noisy_pixels(0.1, 4).is_empty());
+    }
+}
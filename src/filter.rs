@@ -0,0 +1,161 @@
+use std::{fmt::Debug, sync::Arc};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+// decides how much a sample counts towards a pixel, based on how far away from the pixel centre
+// it landed. offsets are in pixels
+pub trait Filter: Send + Sync + Debug {
+    // samples further away than this in either direction are ignored
+    fn radius(&self) -> f32;
+    fn evaluate(&self, dx: f32, dy: f32) -> f32;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterKind {
+    Box,
+    Tent,
+    Gaussian,
+    Mitchell,
+}
+
+impl FilterKind {
+    pub const ALL: [FilterKind; 4] = [
+        FilterKind::Box,
+        FilterKind::Tent,
+        FilterKind::Gaussian,
+        FilterKind::Mitchell,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FilterKind::Box => "box",
+            FilterKind::Tent => "tent",
+            FilterKind::Gaussian => "gaussian",
+            FilterKind::Mitchell => "mitchell",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<FilterKind> {
+        FilterKind::ALL
+            .into_iter()
+            .find(|kind| kind.name() == name.to_lowercase())
+    }
+
+    pub fn build(&self) -> Arc<dyn Filter> {
+        match self {
+            FilterKind::Box => Arc::new(BoxFilter { radius: 0.5 }),
+            FilterKind::Tent => Arc::new(TentFilter { radius: 1.0 }),
+            FilterKind::Gaussian => Arc::new(GaussianFilter {
+                radius: 1.5,
+                sigma: 0.5,
+            }),
+            FilterKind::Mitchell => Arc::new(MitchellFilter {
+                radius: 2.0,
+                b: 1.0 / 3.0,
+                c: 1.0 / 3.0,
+            }),
+        }
+    }
+}
+
+// every sample inside the pixel counts the same, the same as just averaging
+#[derive(Clone, Copy, Debug)]
+pub struct BoxFilter {
+    pub radius: f32,
+}
+
+impl Filter for BoxFilter {
+    fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    fn evaluate(&self, dx: f32, dy: f32) -> f32 {
+        if dx.abs() <= self.radius && dy.abs() <= self.radius {
+            return 1.0;
+        }
+        return 0.0;
+    }
+}
+
+// falls off in a straight line to 0 at the radius
+#[derive(Clone, Copy, Debug)]
+pub struct TentFilter {
+    pub radius: f32,
+}
+
+impl Filter for TentFilter {
+    fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    fn evaluate(&self, dx: f32, dy: f32) -> f32 {
+        (self.radius - dx.abs()).max(0.0) * (self.radius - dy.abs()).max(0.0)
+    }
+}
+
+// a bell curve, shifted down so it reaches 0 at the radius instead of just getting close
+#[derive(Clone, Copy, Debug)]
+pub struct GaussianFilter {
+    pub radius: f32,
+    pub sigma: f32,
+}
+
+impl GaussianFilter {
+    fn gaussian(&self, x: f32) -> f32 {
+        let edge = (-self.radius.powi(2) / (2.0 * self.sigma.powi(2))).exp();
+        ((-x.powi(2) / (2.0 * self.sigma.powi(2))).exp() - edge).max(0.0)
+    }
+}
+
+impl Filter for GaussianFilter {
+    fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    fn evaluate(&self, dx: f32, dy: f32) -> f32 {
+        self.gaussian(dx) * self.gaussian(dy)
+    }
+}
+
+// cubic with small negative lobes that keeps edges sharper than the gaussian, b and c trade off
+// blurring against ringing, 1/3 each is what the paper recommends
+// https://www.cs.utexas.edu/~fussell/courses/cs384g-fall2013/lectures/mitchell/Mitchell.pdf
+#[derive(Clone, Copy, Debug)]
+pub struct MitchellFilter {
+    pub radius: f32,
+    pub b: f32,
+    pub c: f32,
+}
+
+impl MitchellFilter {
+    // defined over -2 to 2
+    fn mitchell(&self, x: f32) -> f32 {
+        let (b, c) = (self.b, self.c);
+        let x = x.abs();
+        if x <= 1.0 {
+            return ((12.0 - 9.0 * b - 6.0 * c) * x.powi(3)
+                + (-18.0 + 12.0 * b + 6.0 * c) * x.powi(2)
+                + (6.0 - 2.0 * b))
+                / 6.0;
+        } else if x <= 2.0 {
+            return ((-b - 6.0 * c) * x.powi(3)
+                + (6.0 * b + 30.0 * c) * x.powi(2)
+                + (-12.0 * b - 48.0 * c) * x
+                + (8.0 * b + 24.0 * c))
+                / 6.0;
+        }
+        return 0.0;
+    }
+}
+
+impl Filter for MitchellFilter {
+    fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    fn evaluate(&self, dx: f32, dy: f32) -> f32 {
+        self.mitchell(2.0 * dx / self.radius) * self.mitchell(2.0 * dy / self.radius)
+    }
+}
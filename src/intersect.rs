@@ -1,11 +1,13 @@
 use std::{cmp::Ordering, fmt::Debug, sync::Arc};
 
-use eframe::egui::Rgba;
+use epaint::Rgba;
+use nalgebra::Vector2;
 
-use crate::{renderer::Ray, surfaces::Surface};
+use crate::{renderer::Ray, scene::description::ItemDescription, surfaces::Surface};
 
 #[derive(Clone, Debug)]
 pub struct TestIntersectionResult(pub Intersection, pub Option<Arc<dyn Surface>>);
+#[allow(clippy::non_canonical_partial_ord_impl)]
 impl PartialOrd for TestIntersectionResult {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         self.0.partial_cmp(&other.0)
@@ -25,8 +27,29 @@ impl Ord for TestIntersectionResult {
 
 impl Eq for TestIntersectionResult {}
 
+impl TestIntersectionResult {
+    // swaps the surface for what it looks like at the hit's uv and colour. this is only done for
+    // the hit that wins, so the other hits along the ray dont each make a new surface
+    pub fn shaded(self) -> TestIntersectionResult {
+        let TestIntersectionResult(intersection, surface) = self;
+        let surface = match (intersection.uv, surface) {
+            (Some(uv), Some(surface)) => Some(surface.at_uv(uv).unwrap_or(surface)),
+            (_, surface) => surface,
+        };
+        let surface = match (intersection.tint, surface) {
+            (Some(tint), Some(surface)) => Some(surface.tinted(tint).unwrap_or(surface)),
+            (_, surface) => surface,
+        };
+        TestIntersectionResult(intersection, surface)
+    }
+}
+
 pub trait Intersect: Send + Sync + Debug {
     fn test_intersection(&self, ray: &Ray, incoming_colour: Rgba) -> TestIntersectionResult;
+    // how to write this object into a scene file, None for things scene files cant have yet
+    fn describe(&self) -> Option<ItemDescription> {
+        None
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -34,6 +57,10 @@ pub struct Intersection {
     pub colour: Rgba,
     pub distance: Option<f32>,
     pub normal: Option<Ray>,
+    // where on the surface's textures the hit is, for things that have texture coordinates
+    pub uv: Option<Vector2<f32>>,
+    // blended from the corners and multiplied into the surface's colour
+    pub tint: Option<Rgba>,
 }
 
 impl Intersection {
@@ -42,6 +69,8 @@ impl Intersection {
             colour,
             distance,
             normal,
+            uv: None,
+            tint: None,
         }
     }
 }
@@ -58,6 +87,7 @@ impl PartialEq for Intersection {
 
 impl Eq for Intersection {}
 
+#[allow(clippy::non_canonical_partial_ord_impl)]
 impl PartialOrd for Intersection {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         match (self.distance, other.distance) {
@@ -0,0 +1,47 @@
+//! a little cpu ray tracer. build a [`Scene`] in code or load one from a json, csv, gltf, obj, ply
+//! or pbrt file, point a [`Camera`] at it and render it to a framebuffer of rows of [`Rgba`] pixels
+//!
+//! ```
+//! use std::sync::Arc;
+//!
+//! use cgraphics::{
+//!     objects::sphere::Sphere, surfaces::diffuse::Diffuse, Camera, RenderSettings, Rgba, Scene,
+//! };
+//! use nalgebra::Vector3;
+//!
+//! let mut scene = Scene::new(2);
+//! scene.add(Arc::new(Sphere::with_shader(
+//!     Vector3::new(0.0, 0.0, 0.0),
+//!     1.0,
+//!     Arc::new(Diffuse::new(Rgba::from_rgb(0.9, 0.3, 0.1), 4)),
+//! )));
+//! let camera = Camera::look_at(Vector3::new(-3.0, 0.0, 0.0), Vector3::zeros(), 32, 24);
+//! let framebuffer = camera.create_buffer(&scene, 1, &RenderSettings::default());
+//! assert_eq!((framebuffer.len(), framebuffer[0].len()), (24, 32));
+//! // Camera::write_image(&framebuffer, "sphere.png", image::ImageFormat::Png) to save it
+//! ```
+//!
+//! the egui viewer lives behind the `viewer` feature, which is on by default
+
+pub mod animation;
+pub mod camera;
+pub mod common_maths;
+pub mod film;
+pub mod filter;
+pub mod intersect;
+pub mod objects;
+pub mod projection;
+pub mod renderer;
+pub mod sampler;
+pub mod scene;
+pub mod settings;
+pub mod stereo;
+pub mod surfaces;
+
+pub use camera::Camera;
+pub use epaint::Rgba;
+pub use intersect::Intersect;
+pub use renderer::Ray;
+pub use scene::Scene;
+pub use settings::RenderSettings;
+pub use surfaces::Surface;
@@ -1,28 +1,59 @@
-use camera::Camera;
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+
+use cgraphics::{
+    camera::Camera,
+    filter::FilterKind,
+    objects::world_light::WorldLight,
+    projection,
+    sampler::SamplerKind,
+    scene::{
+        description::{
+            colour_array, DiffuseDescription, EmissiveDescription, ItemDescription,
+            MetallicRoughnessDescription, SpecularDescription, SurfaceDescription, SurfaceRef,
+            SURFACE_TYPES,
+        },
+        error::Location,
+        materials::Material,
+        watch::Watcher,
+        Scene,
+    },
+    settings::{AdaptiveSettings, IntegratorKind, RenderSettings},
+    stereo::{Stereo, StereoLayout},
+    surfaces::{specular::Specular, Surface},
+};
 use eframe::egui::{self, Key, Rgba};
 use nalgebra::Vector3;
-use renderer::Ray;
-use scene::Scene;
-mod camera;
-mod common_maths;
-mod intersect;
-mod objects;
-mod renderer;
-mod scene;
-mod surfaces;
+
+// how often to look at the scene's files to see if they changed
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+// what the open dialog shows, everything Scene::from_file knows how to load
+const SCENE_EXTENSIONS: [&str; 7] = ["json", "csv", "obj", "gltf", "glb", "ply", "pbrt"];
+
+// where the recently opened files are kept between runs, and how many of them
+const RECENT_FILES: &str = "recent_files";
+const RECENT_LIMIT: usize = 10;
+
+// the scenes built in code, by name
+type Demo = (&'static str, fn() -> Scene);
+const DEMOS: [Demo; 3] = [
+    ("curve", Scene::curve),
+    ("eclipse", Scene::eclipse),
+    ("pondering_orbs", Scene::pondering_orbs),
+];
 
 fn main() -> eframe::Result {
     let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default().with_inner_size([620.0, 620.0]),
+        viewport: egui::ViewportBuilder::default().with_inner_size([620.0, 650.0]),
         ..Default::default()
     };
     eframe::run_native(
         "renderer",
         options,
-        Box::new(|_| {
-            // This gives us image support:
-            Ok(Box::<RenderApp>::default())
-        }),
+        Box::new(|creation| Ok(Box::new(RenderApp::new(creation)))),
     )
 }
 
@@ -31,55 +62,272 @@ struct RenderApp {
     camera: Camera,
     scene: Scene,
     static_frames: u32,
+    settings: RenderSettings,
+    show_heatmap: bool,
+    click_to_focus: bool,
+    // the file the scene came from, which gets loaded again whenever it or anything it pulls in
+    // changes. None for the built in demos
+    scene_file: Option<String>,
+    watcher: Watcher,
+    last_watched: Instant,
+    // why the last open, reload or save didnt work, the old scene stays until one does
+    load_error: Option<String>,
+    // most recent first, kept between runs
+    recent_files: Vec<String>,
+    // the object last clicked on, and what the inspector is editing of it. None if it cant be
+    // written down, so cant be edited either
+    selected: Option<(usize, Option<ItemDescription>)>,
+    // why the last edit couldnt be used
+    edit_error: Option<String>,
 }
 
-impl Default for RenderApp {
-    // runs once at the start
-    fn default() -> Self {
-        // these are the wrong way round teehee
-        let width = 600;
-        let height = 600;
+impl RenderApp {
+    // runs once at the start, opening the file given on the command line
+    fn new(creation: &eframe::CreationContext) -> Self {
+        let recent_files = creation
+            .storage
+            .and_then(|storage| eframe::get_value(storage, RECENT_FILES))
+            .unwrap_or_default();
+        let scene = Scene::new(1);
+        let camera = starting_camera(&scene);
 
         // make the buffer
-        let row = (0..width)
+        let row = (0..camera.width)
             .map(|_| Rgba::from_gray(0.0))
             .collect::<Vec<Rgba>>();
 
-        let buffer = (0..height).map(|_| row.clone()).collect::<Vec<Vec<Rgba>>>();
-
-        // set up camera
-        let ray_location = nalgebra::Vector3::new(-3.0, 0.0, 1.0);
-        let ray_direction = nalgebra::Vector3::new(1.0, 0.0, 0.0);
-        let origin_ray = Ray::new_preserve(ray_location, ray_direction);
-
-        let camera = Camera {
-            location: origin_ray,
-            width,
-            height,
-        };
+        let buffer = (0..camera.height)
+            .map(|_| row.clone())
+            .collect::<Vec<Vec<Rgba>>>();
 
-        RenderApp {
+        let mut app = RenderApp {
             buffer,
             camera,
-            //scene: Scene::from_csv(String::from("blender/test.csv")),
-            //scene: Scene::pondering_orbs(),
-            scene: Scene::from_json("jsons/ci.json"),
+            settings: scene.settings,
+            scene,
             static_frames: 10,
-        }
+            show_heatmap: false,
+            click_to_focus: false,
+            scene_file: None,
+            watcher: Watcher::default(),
+            last_watched: Instant::now(),
+            load_error: None,
+            recent_files,
+            selected: None,
+            edit_error: None,
+        };
+        let scene_file = std::env::args()
+            .nth(1)
+            .unwrap_or(String::from("jsons/ci.json"));
+        app.open(&scene_file);
+        app
+    }
+}
+
+// the scene file gets to pick if it has one
+fn starting_camera(scene: &Scene) -> Camera {
+    match scene.default_camera() {
+        Some(camera) => camera.clone(),
+        None => Camera::look_at(
+            nalgebra::Vector3::new(-3.0, 0.0, 1.0),
+            nalgebra::Vector3::new(-2.0, 0.0, 1.0),
+            600,
+            600,
+        ),
     }
 }
 
 #[allow(deprecated)]
 impl eframe::App for RenderApp {
     fn update(&mut self, ctx: &egui::Context, _: &mut eframe::Frame) {
+        if self.last_watched.elapsed() > WATCH_INTERVAL {
+            self.last_watched = Instant::now();
+            if self.watcher.changed() {
+                self.reload();
+            }
+        }
+        ctx.request_repaint_after(WATCH_INTERVAL);
+        if let Some(error) = &self.load_error {
+            egui::TopBottomPanel::bottom("load error").show(ctx, |ui| {
+                ui.colored_label(egui::Color32::RED, error);
+            });
+        }
+        egui::TopBottomPanel::top("menu").show(ctx, |ui| {
+            egui::menu::bar(ui, |ui| self.menu_bar(ui));
+        });
+        egui::TopBottomPanel::top("settings").show(ctx, |ui| {
+            let settings = &mut self.settings;
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_label("sampler")
+                    .selected_text(settings.sampler.name())
+                    .show_ui(ui, |ui| {
+                        for kind in SamplerKind::ALL {
+                            ui.selectable_value(&mut settings.sampler, kind, kind.name());
+                        }
+                    });
+                ui.add(egui::DragValue::new(&mut settings.seed).prefix("seed: "));
+                ui.add(
+                    egui::DragValue::new(&mut settings.samples_per_pixel)
+                        .range(1..=256)
+                        .prefix("spp: "),
+                );
+                egui::ComboBox::from_label("filter")
+                    .selected_text(settings.filter.name())
+                    .show_ui(ui, |ui| {
+                        for kind in FilterKind::ALL {
+                            ui.selectable_value(&mut settings.filter, kind, kind.name());
+                        }
+                    });
+                egui::ComboBox::from_label("integrator")
+                    .selected_text(settings.integrator.name())
+                    .show_ui(ui, |ui| {
+                        for kind in IntegratorKind::ALL {
+                            ui.selectable_value(&mut settings.integrator, kind, kind.name());
+                        }
+                    });
+            });
+            ui.horizontal(|ui| {
+                let mut adaptive = settings.adaptive.is_some();
+                ui.checkbox(&mut adaptive, "adaptive");
+                settings.adaptive = match (adaptive, settings.adaptive) {
+                    (true, None) => Some(AdaptiveSettings::default()),
+                    (true, current) => current,
+                    (false, _) => None,
+                };
+                if let Some(adaptive) = &mut settings.adaptive {
+                    ui.add(
+                        egui::DragValue::new(&mut adaptive.noise_threshold)
+                            .range(0.001..=1.0)
+                            .speed(0.001)
+                            .prefix("noise: "),
+                    );
+                    ui.add(
+                        egui::DragValue::new(&mut adaptive.max_samples_per_pixel)
+                            .range(1..=1024)
+                            .prefix("max spp: "),
+                    );
+                    ui.checkbox(&mut self.show_heatmap, "heatmap");
+                }
+            });
+            ui.horizontal(|ui| {
+                if !self.scene.cameras.is_empty() {
+                    egui::ComboBox::from_label("camera")
+                        .selected_text("switch to")
+                        .show_ui(ui, |ui| {
+                            for named in &self.scene.cameras {
+                                if ui.selectable_label(false, &named.name).clicked() {
+                                    self.camera = named.camera.clone();
+                                }
+                            }
+                        });
+                }
+                let camera = &mut self.camera;
+                ui.add(
+                    egui::DragValue::new(&mut camera.aperture_radius)
+                        .range(0.0..=10.0)
+                        .speed(0.005)
+                        .prefix("aperture: "),
+                );
+                ui.add(
+                    egui::DragValue::new(&mut camera.focus_distance)
+                        .range(0.01..=1000.0)
+                        .speed(0.05)
+                        .prefix("focus: "),
+                );
+                ui.add(
+                    egui::DragValue::new(&mut camera.aperture_blades)
+                        .range(0..=12)
+                        .prefix("blades: "),
+                );
+                ui.checkbox(&mut self.click_to_focus, "click to focus");
+                egui::ComboBox::from_label("projection")
+                    .selected_text(camera.projection.name())
+                    .show_ui(ui, |ui| {
+                        for name in projection::NAMES {
+                            if ui
+                                .selectable_label(camera.projection.name() == name, name)
+                                .clicked()
+                            {
+                                camera.projection = projection::from_name(name).unwrap();
+                            }
+                        }
+                    });
+            });
+            ui.horizontal(|ui| {
+                let camera = &mut self.camera;
+                let mut stereo = camera.stereo.is_some();
+                ui.checkbox(&mut stereo, "stereo");
+                camera.stereo = match (stereo, camera.stereo) {
+                    (true, None) => Some(Stereo::default()),
+                    (true, current) => current,
+                    (false, _) => None,
+                };
+                if let Some(stereo) = &mut camera.stereo {
+                    ui.add(
+                        egui::DragValue::new(&mut stereo.interpupillary_distance)
+                            .range(0.0..=10.0)
+                            .speed(0.001)
+                            .prefix("ipd: "),
+                    );
+                    ui.add(
+                        egui::DragValue::new(&mut stereo.convergence_distance)
+                            .range(0.01..=1000.0)
+                            .speed(0.05)
+                            .prefix("convergence: "),
+                    );
+                    ui.selectable_value(
+                        &mut stereo.layout,
+                        StereoLayout::SideBySide,
+                        "side by side",
+                    );
+                    ui.selectable_value(&mut stereo.layout, StereoLayout::OverUnder, "over under");
+                }
+                ui.add(
+                    egui::DragValue::new(&mut camera.shutter.0)
+                        .range(0.0..=1.0)
+                        .speed(0.01)
+                        .prefix("shutter open: "),
+                );
+                ui.add(
+                    egui::DragValue::new(&mut camera.shutter.1)
+                        .range(0.0..=1.0)
+                        .speed(0.01)
+                        .prefix("close: "),
+                );
+            });
+        });
+        if self.selected.is_some() {
+            egui::SidePanel::right("inspector").show(ctx, |ui| self.inspector(ui));
+        }
         egui::CentralPanel::default().show(ctx, |ui| {
             self.update_buffer_sharedstate();
             // tell self to tell the camera to render
 
             let framebuffer = self.buffer_to_image();
             //camera::Camera::save_to_file(&self.buffer, Some("recursion"));
-            egui_extras::image::RetainedImage::from_color_image("text", framebuffer)
-                .show_scaled(ui, self.static_frames as f32);
+            let response = egui_extras::image::RetainedImage::from_color_image("text", framebuffer)
+                .show_scaled(ui, self.static_frames as f32)
+                .interact(egui::Sense::click());
+
+            // work out which pixel got clicked on and focus on whatever is there, or pick it to
+            // be inspected
+            if let (true, Some(position)) = (response.clicked(), response.interact_pointer_pos()) {
+                let mut relative = (position - response.rect.min) / response.rect.size();
+                // both eyes are looking at the same thing, so just work out where in the eye
+                match self.camera.stereo.map(|stereo| stereo.layout) {
+                    Some(StereoLayout::SideBySide) => relative.x = (relative.x * 2.0).fract(),
+                    Some(StereoLayout::OverUnder) => relative.y = (relative.y * 2.0).fract(),
+                    None => (),
+                }
+                let x = relative.x * self.camera.width as f32;
+                let y = relative.y * self.camera.height as f32;
+                if self.click_to_focus {
+                    self.camera.focus_on(&self.scene, x, y);
+                    self.static_frames = 10;
+                } else {
+                    self.select(x, y);
+                }
+            }
 
             // handle user inputs
             ctx.input(|inputs| {
@@ -106,9 +354,209 @@ impl eframe::App for RenderApp {
             }
         });
     }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, RECENT_FILES, &self.recent_files);
+    }
 }
 
 impl RenderApp {
+    fn menu_bar(&mut self, ui: &mut egui::Ui) {
+        ui.menu_button("file", |ui| {
+            if ui.button("open…").clicked() {
+                ui.close_menu();
+                let picked = rfd::FileDialog::new()
+                    .add_filter("scenes", &SCENE_EXTENSIONS)
+                    .pick_file();
+                if let Some(path) = picked {
+                    self.open(&path.to_string_lossy());
+                }
+            }
+            ui.add_enabled_ui(!self.recent_files.is_empty(), |ui| {
+                ui.menu_button("open recent", |ui| {
+                    for file in self.recent_files.clone() {
+                        if ui.button(&file).clicked() {
+                            ui.close_menu();
+                            self.open(&file);
+                        }
+                    }
+                });
+            });
+            ui.separator();
+            // only json can be written back, anything else needs somewhere new to go
+            let json_file = self
+                .scene_file
+                .clone()
+                .filter(|file| file.to_lowercase().ends_with(".json"));
+            if ui.button("save").clicked() {
+                ui.close_menu();
+                match json_file {
+                    Some(file) => self.save(&file),
+                    None => self.save_as(),
+                }
+            }
+            if ui.button("save as…").clicked() {
+                ui.close_menu();
+                self.save_as();
+            }
+        });
+        ui.menu_button("demos", |ui| {
+            for (name, build) in DEMOS {
+                if ui.button(name).clicked() {
+                    ui.close_menu();
+                    self.show_scene(build(), None);
+                }
+            }
+        });
+        ui.label(self.scene_file.as_deref().unwrap_or("demo scene"));
+    }
+
+    // swaps to another scene altogether, looking through its own camera
+    fn show_scene(&mut self, scene: Scene, file: Option<String>) {
+        self.camera = starting_camera(&scene);
+        self.settings = scene.settings;
+        self.watcher = Watcher::new(&scene.sources);
+        if let Some(file) = &file {
+            self.watcher.watch(file);
+        }
+        self.scene_file = file;
+        self.scene = scene;
+        self.load_error = None;
+        self.selected = None;
+        self.static_frames = 10;
+    }
+
+    fn open(&mut self, file: &str) {
+        match Scene::from_file(file) {
+            Ok(scene) => {
+                for warning in &scene.warnings {
+                    eprintln!("warning: {}", warning.describe(file));
+                }
+                self.show_scene(scene, Some(file.to_string()));
+                self.remember(file);
+            }
+            Err(error) => self.load_error = Some(error.to_string()),
+        }
+    }
+
+    // puts the file at the top of the recent files, written out in full so it still works when
+    // the viewer gets run from somewhere else
+    fn remember(&mut self, file: &str) {
+        let file = std::fs::canonicalize(file)
+            .map(|path| path.to_string_lossy().into_owned())
+            .unwrap_or(file.to_string());
+        self.recent_files.retain(|recent| *recent != file);
+        self.recent_files.insert(0, file);
+        self.recent_files.truncate(RECENT_LIMIT);
+    }
+
+    fn save(&mut self, file: &str) {
+        if let Err(error) = self.scene.save_json(file) {
+            self.load_error = Some(format!("couldnt save {}: {}", file, error));
+            return;
+        }
+        // the scene file is this one now, and saving it isnt a change that needs reloading
+        self.watcher.touch(file);
+        self.scene_file = Some(file.to_string());
+        self.load_error = None;
+        self.remember(file);
+    }
+
+    fn save_as(&mut self) {
+        let picked = rfd::FileDialog::new()
+            .add_filter("json", &["json"])
+            .set_file_name("scene.json")
+            .save_file();
+        if let Some(path) = picked {
+            self.save(&path.to_string_lossy());
+        }
+    }
+
+    // loads the scene file again, keeping the camera where it is. settings only change if the
+    // file changed them, so ones picked in the window stick around
+    fn reload(&mut self) {
+        let Some(scene_file) = self.scene_file.clone() else {
+            return;
+        };
+        let scene = match Scene::from_file(&scene_file) {
+            Ok(scene) => scene,
+            Err(error) => {
+                // a broken included file should get looked at too, so fixing it reloads
+                for problem in &error.problems {
+                    if let Location::InFile(file, _) = &problem.location {
+                        self.watcher.watch(file);
+                    }
+                }
+                self.load_error = Some(error.to_string());
+                return;
+            }
+        };
+        if scene.settings != self.scene.settings {
+            self.settings = scene.settings;
+        }
+        self.watcher = Watcher::new(&scene.sources);
+        self.watcher.watch(&scene_file);
+        self.scene = scene;
+        self.load_error = None;
+        // the file probably still has the same things in the same order, so keep inspecting it
+        if let Some((index, _)) = self.selected {
+            self.selected = (index < self.scene.objects().len())
+                .then(|| (index, self.scene.describe_item(index)));
+        }
+        self.static_frames = 10;
+    }
+
+    // picks whatever is under the pixel to show in the inspector, or nothing if the click missed
+    fn select(&mut self, x: f32, y: f32) {
+        let ray = self
+            .camera
+            .pixel_ray(x, y, self.camera.width, self.camera.height, (0.5, 0.5));
+        self.selected = ray
+            .and_then(|ray| self.scene.pick(&ray))
+            .map(|index| (index, self.scene.describe_item(index)));
+        self.edit_error = None;
+    }
+
+    // the picked object's numbers and surface, which get put back into the scene as they change
+    fn inspector(&mut self, ui: &mut egui::Ui) {
+        let mut close = false;
+        ui.horizontal(|ui| {
+            ui.heading("inspector");
+            close = ui.button("close").clicked();
+        });
+        if close {
+            self.selected = None;
+            return;
+        }
+        let Some((index, item)) = &mut self.selected else {
+            return;
+        };
+        let Some(item) = item else {
+            ui.label("this cant be written down, so it cant be edited");
+            return;
+        };
+        let before = item.clone();
+        let locked = self.scene.why_not_editable(*index);
+        if let Some(why) = locked {
+            ui.label(why);
+        }
+        ui.add_enabled_ui(locked.is_none(), |ui| {
+            item_ui(ui, item, &self.scene.materials)
+        });
+        if *item != before {
+            match self.scene.update_item(*index, item) {
+                Ok(()) => {
+                    self.edit_error = None;
+                    self.static_frames = 10;
+                }
+                Err(reason) => self.edit_error = Some(reason.to_string()),
+            }
+        }
+        if let Some(error) = &self.edit_error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+    }
+
     // takes in the vector and makes a colorImage from it
     fn buffer_to_image(&self) -> egui::ColorImage {
         let mut flattened = vec![];
@@ -122,13 +570,217 @@ impl RenderApp {
         }
         egui::ColorImage::from_rgba_unmultiplied(
             [self.buffer[0].len(), self.buffer.len()],
-            &flattened.as_slice(),
+            flattened.as_slice(),
         )
     }
 
     // tells the camera to do the rendering, parallel is slower than sequential for now
     fn update_buffer_sharedstate(&mut self) {
         //self.buffer = self.camera.create_buffer_parallel(self.scene.clone());
-        self.buffer = self.camera.create_buffer(&self.scene, self.static_frames);
+        let buffers = self
+            .camera
+            .eyes()
+            .iter()
+            .map(|eye| {
+                let film = eye.create_film(&self.scene, self.static_frames, &self.settings);
+                if self.show_heatmap && self.settings.adaptive.is_some() {
+                    film.sample_heatmap()
+                } else {
+                    film.resolve()
+                }
+            })
+            .collect();
+        self.buffer = self.camera.combine_eyes(buffers);
+    }
+}
+
+fn item_ui(ui: &mut egui::Ui, item: &mut ItemDescription, materials: &BTreeMap<String, Material>) {
+    let kind = match item {
+        ItemDescription::Sphere(_) => "sphere",
+        ItemDescription::Triangle(_) => "triangle",
+        ItemDescription::Quad(_) => "quad",
+        ItemDescription::Plane(_) => "plane",
+        ItemDescription::PointLight(_) => "point light",
+        ItemDescription::WorldLight(_) => "world light",
+    };
+    match item.name() {
+        Some(name) => ui.label(format!("{} {}", kind, name)),
+        None => ui.label(kind),
+    };
+    if item.transforms() != (None, None) {
+        ui.label("moved by a transform, these are from before it");
+    }
+    match item {
+        ItemDescription::Sphere(sphere) => {
+            vector_ui(ui, "origin", &mut sphere.origin);
+            ui.add(
+                egui::DragValue::new(&mut sphere.radius)
+                    .range(0.0..=f32::MAX)
+                    .speed(0.01)
+                    .prefix("radius: "),
+            );
+        }
+        ItemDescription::Triangle(triangle) => {
+            for (name, corner) in [
+                ("a", &mut triangle.a),
+                ("b", &mut triangle.b),
+                ("c", &mut triangle.c),
+            ] {
+                vector_ui(ui, name, corner);
+            }
+        }
+        ItemDescription::Quad(quad) => {
+            for (name, corner) in [("a", &mut quad.a), ("b", &mut quad.b), ("c", &mut quad.c)] {
+                vector_ui(ui, name, corner);
+            }
+        }
+        ItemDescription::Plane(plane) => {
+            for (name, corner) in [
+                ("a", &mut plane.a),
+                ("b", &mut plane.b),
+                ("c", &mut plane.c),
+            ] {
+                vector_ui(ui, name, corner);
+            }
+        }
+        ItemDescription::PointLight(light) => {
+            vector_ui(ui, "origin", &mut light.origin);
+            ui.add(
+                egui::DragValue::new(&mut light.intensity)
+                    .range(0.0..=f32::MAX)
+                    .speed(0.01)
+                    .prefix("intensity: "),
+            );
+        }
+        ItemDescription::WorldLight(light) => {
+            // left out means the default sky, so start from that
+            let default = WorldLight::default();
+            for (name, colour, fallback) in [
+                ("top", &mut light.top, default.top),
+                ("bottom", &mut light.bottom, default.bottom),
+            ] {
+                let mut edited = colour.unwrap_or(colour_array(fallback));
+                colour_ui(ui, name, &mut edited);
+                if edited != colour.unwrap_or(colour_array(fallback)) {
+                    *colour = Some(edited);
+                }
+            }
+        }
+    }
+    if let Some(surface) = item.surface_mut() {
+        ui.separator();
+        surface_ui(ui, surface, materials);
+    }
+}
+
+// either one of the scene's materials, which are shared so dont get edited here, or the item's own
+// surface
+fn surface_ui(
+    ui: &mut egui::Ui,
+    surface: &mut Option<SurfaceRef>,
+    materials: &BTreeMap<String, Material>,
+) {
+    let material = match surface {
+        Some(SurfaceRef::Material(name)) => Some(name.clone()),
+        _ => None,
+    };
+    egui::ComboBox::from_label("surface")
+        .selected_text(material.as_deref().unwrap_or("its own"))
+        .show_ui(ui, |ui| {
+            if ui.selectable_label(material.is_none(), "its own").clicked() {
+                // starting from the material it was using
+                if let Some(material) = material.as_ref().and_then(|name| materials.get(name)) {
+                    *surface = Some(SurfaceRef::Surface(material.surface.describe()));
+                }
+            }
+            for name in materials.keys() {
+                if ui
+                    .selectable_label(material.as_ref() == Some(name), name)
+                    .clicked()
+                {
+                    *surface = Some(SurfaceRef::Material(name.clone()));
+                }
+            }
+        });
+    let mut own = match surface {
+        Some(SurfaceRef::Material(_)) => return,
+        Some(SurfaceRef::Surface(own)) => own.clone(),
+        // what scene files give items that dont say
+        None => Specular::new().describe(),
+    };
+    let before = own.clone();
+
+    let (kind, colour) = match &own {
+        SurfaceDescription::Specular(specular) => ("specular", specular.colour),
+        SurfaceDescription::Diffuse(diffuse) => ("diffuse", diffuse.colour),
+        SurfaceDescription::MetallicRoughness(surface) => ("metallic_roughness", surface.colour),
+        SurfaceDescription::Emissive(emissive) => ("emissive", emissive.colour),
+    };
+    egui::ComboBox::from_label("type")
+        .selected_text(kind)
+        .show_ui(ui, |ui| {
+            for name in SURFACE_TYPES {
+                if ui.selectable_label(kind == name, name).clicked() && kind != name {
+                    own = surface_of_type(name, colour);
+                }
+            }
+        });
+    match &mut own {
+        SurfaceDescription::Specular(specular) => colour_ui(ui, "colour", &mut specular.colour),
+        SurfaceDescription::Diffuse(diffuse) => {
+            colour_ui(ui, "colour", &mut diffuse.colour);
+            samples_ui(ui, &mut diffuse.samples);
+        }
+        SurfaceDescription::MetallicRoughness(surface) => {
+            colour_ui(ui, "colour", &mut surface.colour);
+            ui.add(egui::Slider::new(&mut surface.metallic, 0.0..=1.0).text("metallic"));
+            ui.add(egui::Slider::new(&mut surface.roughness, 0.0..=1.0).text("roughness"));
+            samples_ui(ui, &mut surface.samples);
+        }
+        SurfaceDescription::Emissive(emissive) => colour_ui(ui, "colour", &mut emissive.colour),
     }
+    if own != before {
+        *surface = Some(SurfaceRef::Surface(own));
+    }
+}
+
+// a fresh surface of another type, keeping the colour it had
+fn surface_of_type(name: &str, colour: [f32; 3]) -> SurfaceDescription {
+    match name {
+        "diffuse" => SurfaceDescription::Diffuse(DiffuseDescription { colour, samples: 3 }),
+        "metallic_roughness" => {
+            SurfaceDescription::MetallicRoughness(MetallicRoughnessDescription {
+                colour,
+                metallic: 0.0,
+                roughness: 0.5,
+                samples: 3,
+            })
+        }
+        "emissive" => SurfaceDescription::Emissive(EmissiveDescription { colour }),
+        _ => SurfaceDescription::Specular(SpecularDescription { colour }),
+    }
+}
+
+fn vector_ui(ui: &mut egui::Ui, name: &str, vector: &mut [f32; 3]) {
+    ui.horizontal(|ui| {
+        ui.label(name);
+        for value in vector {
+            ui.add(egui::DragValue::new(value).speed(0.05));
+        }
+    });
+}
+
+fn colour_ui(ui: &mut egui::Ui, name: &str, colour: &mut [f32; 3]) {
+    ui.horizontal(|ui| {
+        ui.color_edit_button_rgb(colour);
+        ui.label(name);
+    });
+}
+
+fn samples_ui(ui: &mut egui::Ui, samples: &mut usize) {
+    ui.add(
+        egui::DragValue::new(samples)
+            .range(1..=64)
+            .prefix("samples: "),
+    );
 }
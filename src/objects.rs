@@ -2,5 +2,6 @@ pub mod light;
 pub mod plane;
 pub mod quad;
 pub mod sphere;
+pub mod transformed;
 pub mod triangle;
 pub mod world_light;
@@ -1,8 +1,9 @@
-use eframe::egui::Rgba;
+use epaint::Rgba;
 
 use crate::{
     intersect::{Intersect, Intersection, TestIntersectionResult},
     renderer,
+    scene::description::{ItemDescription, PointLightDescription},
 };
 
 #[derive(Clone, Copy, Debug)]
@@ -54,4 +55,14 @@ impl Intersect for PointLight {
             );
         }
     }
+
+    fn describe(&self) -> Option<ItemDescription> {
+        Some(ItemDescription::PointLight(PointLightDescription {
+            name: None,
+            origin: self.origin.into(),
+            intensity: self.intensity,
+            transform: None,
+            transform_end: None,
+        }))
+    }
 }
@@ -1,11 +1,12 @@
 use std::sync::Arc;
 
-use eframe::egui::Rgba;
+use epaint::Rgba;
 use nalgebra::{Matrix3, Vector3};
 
 use crate::{
     intersect::{Intersect, Intersection, TestIntersectionResult},
     renderer::Ray,
+    scene::description::{ItemDescription, PlaneDescription, SurfaceRef},
     surfaces::{diffuse, Surface},
 };
 
@@ -17,6 +18,8 @@ pub struct Plane {
     j: Vector3<f32>,
     k: f32,
     origin: Vector3<f32>,
+    // the points it was made from, so it can be written back out exactly
+    corners: [Vector3<f32>; 3],
     inverse: Option<Matrix3<f32>>,
     surface: Arc<dyn Surface>,
 }
@@ -37,6 +40,7 @@ impl Plane {
             j,
             k,
             origin: A.clone_owned(),
+            corners: [A.clone_owned(), B.clone_owned(), C.clone_owned()],
             inverse: simul_eq.try_inverse(),
             surface: Arc::new(diffuse::Diffuse {
                 colour: Rgba::BLUE,
@@ -57,6 +61,14 @@ impl Plane {
         return temp;
     }
 
+    pub fn corners(&self) -> [Vector3<f32>; 3] {
+        self.corners
+    }
+
+    pub fn surface(&self) -> &Arc<dyn Surface> {
+        &self.surface
+    }
+
     pub fn in_plane_coords(&self, point: &Vector3<f32>) -> Option<Vector3<f32>> {
         if let Some(inv) = self.inverse {
             let return_maybe = point.transpose() * inv;
@@ -91,4 +103,17 @@ impl Intersect for Plane {
             Some(self.surface.clone()),
         );
     }
+
+    fn describe(&self) -> Option<ItemDescription> {
+        let [a, b, c] = self.corners;
+        Some(ItemDescription::Plane(PlaneDescription {
+            name: None,
+            a: a.into(),
+            b: b.into(),
+            c: c.into(),
+            surface: Some(SurfaceRef::Surface(self.surface.describe())),
+            transform: None,
+            transform_end: None,
+        }))
+    }
 }
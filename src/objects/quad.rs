@@ -6,6 +6,7 @@ use nalgebra::Vector3;
 use crate::{
     intersect::{Intersect, Intersection, TestIntersectionResult},
     objects::plane::Plane,
+    scene::description::{ItemDescription, QuadDescription, SurfaceRef},
     surfaces::{diffuse::Diffuse, Surface},
 };
 
@@ -63,8 +64,23 @@ impl Intersect for Quad {
                 colour: Rgba::BLACK,
                 distance: None,
                 normal: None,
+                uv: None,
+                tint: None,
             },
             None,
         );
     }
+
+    fn describe(&self) -> Option<ItemDescription> {
+        let [a, b, c] = self.inner_plane.corners();
+        Some(ItemDescription::Quad(QuadDescription {
+            name: None,
+            a: a.into(),
+            b: b.into(),
+            c: c.into(),
+            surface: Some(SurfaceRef::Surface(self.surface.describe())),
+            transform: None,
+            transform_end: None,
+        }))
+    }
 }
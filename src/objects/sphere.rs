@@ -1,11 +1,12 @@
 use std::sync::Arc;
 
-use eframe::egui::Rgba;
+use epaint::Rgba;
 use nalgebra;
 
 use crate::{
     intersect::{Intersect, Intersection, TestIntersectionResult},
     renderer::Ray,
+    scene::description::{ItemDescription, SphereDescription, SurfaceRef},
     surfaces::{specular::Specular, Surface},
 };
 
@@ -13,6 +14,8 @@ use crate::{
 pub struct Sphere {
     pub origin: nalgebra::Vector3<f32>,
     pub radius: f32,
+    // how far the centre moves per unit of time, the origin is where it is at time 0
+    pub velocity: nalgebra::Vector3<f32>,
     surface: Arc<dyn Surface>,
 }
 
@@ -20,7 +23,8 @@ pub struct Sphere {
 impl Intersect for Sphere {
     // https://www.scratchapixel.com/lessons/3d-basic-rendering/minimal-ray-tracer-rendering-simple-shapes/ray-Sphere-intersection.html
     fn test_intersection(&self, ray: &Ray, colour: Rgba) -> TestIntersectionResult {
-        let L = self.origin - ray.origin;
+        let centre = self.centre_at(ray.time);
+        let L = centre - ray.origin;
         let t_ca = L.dot(&ray.direction);
 
         // return black if the sphere is behind the camera
@@ -33,14 +37,14 @@ impl Intersect for Sphere {
         }
 
         let close_approach_point = ray.at_point(t_ca); // closest approach
-        let distance = (close_approach_point - self.origin).norm();
+        let distance = (close_approach_point - centre).norm();
 
         let t_surface_to_cap = (self.radius.powi(2) - distance.powi(2)).sqrt(); // how far it is to reach close approach from the surface for the ray
         let t_surface = t_ca - t_surface_to_cap;
         let surface = ray.at_point(t_surface); // find the location of the intersection in world coordinates
 
         // the normal is in the same direction as the radius to the surface
-        let normal_vec = surface - self.origin;
+        let normal_vec = surface - centre;
         let normal_vec = normal_vec / normal_vec.norm();
 
         let normal_ray = Ray::new(surface, normal_vec);
@@ -61,13 +65,31 @@ impl Intersect for Sphere {
             None,
         );
     }
+
+    fn describe(&self) -> Option<ItemDescription> {
+        Some(ItemDescription::Sphere(SphereDescription {
+            name: None,
+            origin: self.origin.into(),
+            radius: self.radius,
+            velocity: (self.velocity != nalgebra::Vector3::zeros()).then(|| self.velocity.into()),
+            surface: Some(SurfaceRef::Surface(self.surface.describe())),
+            transform: None,
+            transform_end: None,
+        }))
+    }
 }
 
 impl Sphere {
+    // the shutter only opens between 0 and 1, the same as for transformed objects
+    pub fn centre_at(&self, time: f32) -> nalgebra::Vector3<f32> {
+        self.origin + self.velocity * time.clamp(0.0, 1.0)
+    }
+
     pub fn blank_specular_surface(origin: nalgebra::Vector3<f32>, radius: f32) -> Sphere {
         Sphere {
             origin,
             radius,
+            velocity: nalgebra::Vector3::zeros(),
             surface: Arc::new(Specular::new()),
         }
     }
@@ -79,6 +101,7 @@ impl Sphere {
         Sphere {
             origin,
             radius,
+            velocity: nalgebra::Vector3::zeros(),
             surface,
         }
     }
@@ -0,0 +1,128 @@
+use std::sync::Arc;
+
+use epaint::Rgba;
+use nalgebra::Point3;
+
+use crate::{
+    common_maths::transform::Transform,
+    intersect::{Intersect, TestIntersectionResult},
+    renderer::Ray,
+    scene::description::{ItemDescription, TransformDescription},
+};
+
+// puts any other object somewhere else. it can also move while the shutter is open, going from
+// start at time 0 to end at time 1
+#[derive(Clone, Debug)]
+pub struct Transformed {
+    pub object: Arc<dyn Intersect>,
+    pub start: Transform,
+    pub end: Transform,
+}
+
+impl Transformed {
+    pub fn new(object: Arc<dyn Intersect>, transform: Transform) -> Transformed {
+        Transformed {
+            object,
+            start: transform,
+            end: transform,
+        }
+    }
+
+    pub fn moving(object: Arc<dyn Intersect>, start: Transform, end: Transform) -> Transformed {
+        Transformed { object, start, end }
+    }
+
+    pub fn transform_at(&self, time: f32) -> Transform {
+        self.start.lerp(&self.end, time.clamp(0.0, 1.0))
+    }
+}
+
+impl Intersect for Transformed {
+    // rather than moving the object, move the ray the opposite way into the object's own space,
+    // then bring whatever it hit back out again
+    fn test_intersection(&self, ray: &Ray, colour: Rgba) -> TestIntersectionResult {
+        let transform = self.transform_at(ray.time).to_similarity();
+        let inverse = transform.inverse();
+        let local_ray = Ray::new(
+            (inverse * Point3::from(ray.origin)).coords,
+            inverse * ray.direction,
+        )
+        .at_time(ray.time);
+
+        let TestIntersectionResult(mut intersection, surface) =
+            self.object.test_intersection(&local_ray, colour);
+        if let (Some(distance), Some(normal)) = (intersection.distance, intersection.normal) {
+            // the scale changes how far away it is, so work it out again from the hit point
+            let hit = transform * Point3::from(local_ray.at_point(distance));
+            intersection.distance = Some((hit.coords - ray.origin).norm());
+            intersection.normal = Some(Ray::new(
+                (transform * Point3::from(normal.origin)).coords,
+                transform.isometry.rotation * normal.direction,
+            ));
+        }
+        return TestIntersectionResult(intersection, surface);
+    }
+
+    // the object with its transforms on. something already transformed inside another transform,
+    // or that cant have a transform, cant be written down
+    fn describe(&self) -> Option<ItemDescription> {
+        let mut item = self.object.describe()?;
+        let (start, end) = item.transforms_mut()?;
+        if start.is_some() || end.is_some() {
+            return None;
+        }
+        *start = Some(TransformDescription::describe(&self.start));
+        if self.end != self.start {
+            *end = Some(TransformDescription::describe(&self.end));
+        }
+        Some(item)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use epaint::Rgba;
+    use nalgebra::Vector3;
+
+    use crate::{
+        common_maths::transform::Transform, intersect::Intersect, objects::sphere::Sphere,
+        renderer::Ray,
+    };
+
+    use super::Transformed;
+
+    #[test]
+    fn moving_sphere_and_moving_transform_agree() {
+        let velocity = Vector3::new(0.0, 2.0, 0.0);
+        let mut moving = Sphere::blank_specular_surface(Vector3::new(5.0, 0.0, 0.0), 1.0);
+        moving.velocity = velocity;
+        let transformed = Transformed::moving(
+            Arc::new(Sphere::blank_specular_surface(Vector3::zeros(), 0.5)),
+            Transform {
+                translation: Vector3::new(5.0, 0.0, 0.0),
+                scale: 2.0,
+                ..Transform::default()
+            },
+            Transform {
+                translation: Vector3::new(5.0, 0.0, 0.0) + velocity,
+                scale: 2.0,
+                ..Transform::default()
+            },
+        );
+
+        // past the end of the shutter they both stay where they got to
+        for (time, y) in [(0.0, 0.0), (0.5, 1.0), (1.0, 2.0), (-1.0, 0.0), (3.0, 2.0)] {
+            let ray =
+                Ray::new(Vector3::new(0.0, y, 0.0), Vector3::new(1.0, 0.0, 0.0)).at_time(time);
+            for object in [&moving as &dyn Intersect, &transformed] {
+                let hit = object.test_intersection(&ray, Rgba::BLACK).0;
+                let distance = hit.distance.unwrap();
+                assert!((distance - 4.0).abs() < 1e-4, "{} at {}", distance, time);
+                let normal = hit.normal.unwrap().direction;
+                assert!((normal - Vector3::new(-1.0, 0.0, 0.0)).norm() < 1e-4);
+            }
+        }
+    }
+}
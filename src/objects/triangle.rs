@@ -1,21 +1,34 @@
 use std::sync::Arc;
 
-use eframe::egui::Rgba;
-use nalgebra::Vector3;
+use epaint::Rgba;
+use nalgebra::{Vector2, Vector3};
 
 use crate::{
     intersect::{Intersect, Intersection, TestIntersectionResult},
     objects::plane::Plane,
-    surfaces::Surface,
+    scene::description::{colour_array, ItemDescription, SurfaceRef, TriangleDescription},
+    surfaces::{diffuse::Diffuse, Surface},
 };
 
 #[derive(Clone, Debug)]
 pub struct Triangle {
     inner_plane: Plane,
-    colour: Rgba,
+    pub shading: Shading,
+}
+
+// what meshes know about each corner on top of where it is, in the order a, b, c
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Shading {
+    // blended across the triangle so that a mesh looks smooth rather than faceted
+    pub normals: Option<[Vector3<f32>; 3]>,
+    // where each corner is on the surface's textures
+    pub uvs: Option<[Vector2<f32>; 3]>,
+    // blended across the triangle and multiplied into the surface's colour, like scans have
+    pub colours: Option<[Rgba; 3]>,
 }
 
 impl Triangle {
+    // a plain diffuse triangle of that colour
     #[allow(non_snake_case)]
     pub fn from_3_points(
         A: &Vector3<f32>,
@@ -23,10 +36,7 @@ impl Triangle {
         C: &Vector3<f32>,
         colour: Rgba,
     ) -> Triangle {
-        Triangle {
-            inner_plane: Plane::from_3_points(A, B, C),
-            colour,
-        }
+        Triangle::from_3_points_and_surface(A, B, C, Arc::new(Diffuse::new(colour, 3)))
     }
 
     #[allow(non_snake_case)]
@@ -38,9 +48,14 @@ impl Triangle {
     ) -> Triangle {
         Triangle {
             inner_plane: Plane::from_3_points_and_surface(A, B, C, surface),
-            colour: Rgba::BLACK,
+            shading: Shading::default(),
         }
     }
+
+    pub fn with_shading(mut self, shading: Shading) -> Triangle {
+        self.shading = shading;
+        self
+    }
 }
 
 impl Intersect for Triangle {
@@ -59,7 +74,34 @@ impl Intersect for Triangle {
                 //println!("{:?}", v);
                 let bounded = |h: f32| h > 0.0 && h < 1.0;
                 if bounded(sum) && bounded(v.x) && bounded(v.y) {
-                    potential.colour = self.colour;
+                    potential.colour = Rgba::BLACK;
+                    // v.x and v.y are how far towards b and c the hit is
+                    let weights = [1.0 - sum, v.x, v.y];
+                    if let (Some(normals), Some(normal)) =
+                        (self.shading.normals, potential.normal.as_mut())
+                    {
+                        let smooth = (0..3)
+                            .map(|i| normals[i] * weights[i])
+                            .sum::<Vector3<f32>>()
+                            .normalize();
+                        // stay on the same side as the flat normal so bounces still leave the
+                        // right way
+                        normal.direction = if smooth.dot(&normal.direction) < 0.0 {
+                            -smooth
+                        } else {
+                            smooth
+                        };
+                    }
+                    // the scene puts these onto the surface once it knows this is the closest hit
+                    potential.uv = self
+                        .shading
+                        .uvs
+                        .map(|uvs| (0..3).map(|i| uvs[i] * weights[i]).sum());
+                    potential.tint = self.shading.colours.map(|colours| {
+                        (0..3).fold(Rgba::TRANSPARENT, |sum, i| {
+                            sum + colours[i].multiply(weights[i])
+                        })
+                    });
                     return TestIntersectionResult(potential, surface);
                 }
             }
@@ -69,8 +111,32 @@ impl Intersect for Triangle {
                 colour: Rgba::BLACK,
                 distance: None,
                 normal: None,
+                uv: None,
+                tint: None,
             },
             None,
         );
     }
+
+    fn describe(&self) -> Option<ItemDescription> {
+        let [a, b, c] = self.inner_plane.corners();
+        Some(ItemDescription::Triangle(TriangleDescription {
+            name: None,
+            a: a.into(),
+            b: b.into(),
+            c: c.into(),
+            surface: Some(SurfaceRef::Surface(self.inner_plane.surface().describe())),
+            normals: self
+                .shading
+                .normals
+                .map(|normals| normals.map(|normal| normal.into())),
+            uvs: self.shading.uvs.map(|uvs| uvs.map(|uv| uv.into())),
+            colours: self
+                .shading
+                .colours
+                .map(|colours| colours.map(colour_array)),
+            transform: None,
+            transform_end: None,
+        }))
+    }
 }
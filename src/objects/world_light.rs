@@ -3,10 +3,31 @@ use epaint::Rgba;
 use crate::{
     common_maths::maths,
     intersect::{Intersect, Intersection, TestIntersectionResult},
+    scene::description::{colour_array, ItemDescription, WorldLightDescription},
 };
 
-#[derive(Debug)]
-pub struct WorldLight {}
+// light coming in from every direction infinitely far away, fading from the bottom colour when
+// looking straight down to the top colour when looking straight up
+#[derive(Clone, Copy, Debug)]
+pub struct WorldLight {
+    pub top: Rgba,
+    pub bottom: Rgba,
+}
+
+impl Default for WorldLight {
+    fn default() -> Self {
+        WorldLight {
+            top: Rgba::from_gray(0.2),
+            bottom: Rgba::BLACK,
+        }
+    }
+}
+
+impl WorldLight {
+    pub fn new(top: Rgba, bottom: Rgba) -> WorldLight {
+        WorldLight { top, bottom }
+    }
+}
 
 impl Intersect for WorldLight {
     fn test_intersection(
@@ -14,13 +35,24 @@ impl Intersect for WorldLight {
         ray: &crate::renderer::Ray,
         _: epaint::Rgba,
     ) -> TestIntersectionResult {
+        let up = 0.5 * (1.0 + maths::normalise_vec3(&ray.direction).z);
         return TestIntersectionResult(
             Intersection {
-                colour: Rgba::from_gray(0.1 * (1.0 + maths::normalise_vec3(&ray.direction).z)),
+                colour: self.bottom.multiply(1.0 - up) + self.top.multiply(up),
                 distance: Some(f32::MAX),
                 normal: None,
+                uv: None,
+                tint: None,
             },
             None,
         );
     }
+
+    fn describe(&self) -> Option<ItemDescription> {
+        Some(ItemDescription::WorldLight(WorldLightDescription {
+            name: None,
+            top: Some(colour_array(self.top)),
+            bottom: Some(colour_array(self.bottom)),
+        }))
+    }
 }
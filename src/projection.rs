@@ -0,0 +1,98 @@
+use std::{fmt::Debug, sync::Arc};
+
+use crate::{camera::Camera, renderer::Ray, scene::description::ProjectionDescription};
+
+pub mod equirectangular;
+pub mod fisheye;
+pub mod orthographic;
+pub mod perspective;
+
+// turns a point on the screen into a ray leaving the camera. x and y go from -1 to 1 with y
+// pointing up, aspect is width over height. lens is a point in the unit square for projections
+// that have a lens to sample. anything off the edge of what the projection can see gives None
+pub trait Projection: Send + Sync + Debug {
+    fn generate_ray(
+        &self,
+        camera: &Camera,
+        x: f32,
+        y: f32,
+        aspect: f32,
+        lens: (f32, f32),
+    ) -> Option<Ray>;
+    fn name(&self) -> &'static str;
+    // for saving the camera
+    fn describe(&self) -> ProjectionDescription;
+    // for stereo, the same projection for an eye that is offset sideways by this much. None means
+    // the camera can just be moved over instead
+    fn for_eye(&self, _offset: f32) -> Option<Arc<dyn Projection>> {
+        None
+    }
+}
+
+pub const NAMES: [&str; 4] = ["perspective", "orthographic", "fisheye", "equirectangular"];
+
+// each kind of projection with its default settings
+pub fn from_name(name: &str) -> Option<Arc<dyn Projection>> {
+    match name {
+        "perspective" => Some(Arc::new(perspective::Perspective {})),
+        "orthographic" => Some(Arc::new(orthographic::Orthographic::new(4.0))),
+        "fisheye" => Some(Arc::new(fisheye::Fisheye::new(180.0))),
+        "equirectangular" => Some(Arc::new(equirectangular::Equirectangular::new())),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use nalgebra::Vector3;
+
+    use crate::{camera::Camera, projection};
+
+    #[test]
+    fn projections_look_the_right_way() {
+        let camera = Camera::look_at(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.0, 0.0),
+            20,
+            10,
+        );
+        let direction = |name: &str, x: f32, y: f32| {
+            projection::from_name(name)
+                .unwrap()
+                .generate_ray(&camera, x, y, 2.0, (0.5, 0.5))
+                .map(|ray| ray.direction)
+        };
+        let close = |a: Option<Vector3<f32>>, b: Vector3<f32>| (a.unwrap() - b).norm() < 1e-5;
+
+        for name in projection::NAMES {
+            assert!(close(
+                direction(name, 0.0, 0.0),
+                Vector3::new(1.0, 0.0, 0.0)
+            ));
+        }
+        // orthographic never spreads out
+        assert!(close(
+            direction("orthographic", 0.7, -0.3),
+            Vector3::new(1.0, 0.0, 0.0)
+        ));
+        // the edge of a 180 degree fisheye is side on, the corners are outside the circle
+        assert!(close(
+            direction("fisheye", 0.5, 0.0),
+            Vector3::new(0.0, -1.0, 0.0)
+        ));
+        assert!(direction("fisheye", 1.0, 1.0).is_none());
+        // panoramas go all the way round and all the way up
+        assert!(close(
+            direction("equirectangular", 1.0, 0.0),
+            Vector3::new(-1.0, 0.0, 0.0)
+        ));
+        assert!(close(
+            direction("equirectangular", 0.5, 0.0),
+            Vector3::new(0.0, -1.0, 0.0)
+        ));
+        assert!(close(
+            direction("equirectangular", 0.3, 1.0),
+            Vector3::new(0.0, 0.0, 1.0)
+        ));
+    }
+}
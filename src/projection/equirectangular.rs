@@ -0,0 +1,50 @@
+use std::{
+    f32::consts::{FRAC_PI_2, PI},
+    sync::Arc,
+};
+
+use crate::{
+    camera::Camera, projection::Projection, renderer::Ray,
+    scene::description::ProjectionDescription,
+};
+
+// the whole sphere around the camera, left to right is all the way around and top to bottom is
+// straight up to straight down. this is the layout vr panoramas and environment maps want, so use
+// a 2:1 picture. forwards ends up in the middle
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Equirectangular {
+    // for omni-directional stereo, every ray starts off to the side of the direction it is
+    // looking by this much (negative for the left eye), so both eyes are the right distance apart
+    // whichever way you turn your head. 0 for a normal panorama
+    pub eye_offset: f32,
+}
+
+impl Equirectangular {
+    pub fn new() -> Equirectangular {
+        Equirectangular { eye_offset: 0.0 }
+    }
+}
+
+impl Projection for Equirectangular {
+    fn generate_ray(&self, camera: &Camera, x: f32, y: f32, _: f32, _: (f32, f32)) -> Option<Ray> {
+        let longitude = x * PI;
+        let latitude = y * FRAC_PI_2;
+        let (forward, right, up) = camera.basis();
+        let around = forward * longitude.cos() + right * longitude.sin();
+        let direction = around * latitude.cos() + up * latitude.sin();
+        let sideways = around.cross(&up) * self.eye_offset;
+        Some(Ray::new(camera.position + sideways, direction))
+    }
+
+    fn name(&self) -> &'static str {
+        "equirectangular"
+    }
+
+    fn describe(&self) -> ProjectionDescription {
+        ProjectionDescription::Equirectangular
+    }
+
+    fn for_eye(&self, offset: f32) -> Option<Arc<dyn Projection>> {
+        Some(Arc::new(Equirectangular { eye_offset: offset }))
+    }
+}
@@ -0,0 +1,54 @@
+use crate::{
+    camera::Camera,
+    projection::Projection,
+    renderer::Ray,
+    scene::description::{FisheyeDescription, ProjectionDescription},
+};
+
+// equidistant fisheye, the angle away from forwards goes up evenly with the distance from the
+// middle of the picture. the circle touches the top and bottom and everything outside it is black
+#[derive(Clone, Copy, Debug)]
+pub struct Fisheye {
+    // in degrees, across the whole circle
+    pub fov: f32,
+}
+
+impl Fisheye {
+    pub fn new(fov: f32) -> Fisheye {
+        Fisheye { fov }
+    }
+}
+
+impl Projection for Fisheye {
+    fn generate_ray(
+        &self,
+        camera: &Camera,
+        x: f32,
+        y: f32,
+        aspect: f32,
+        _: (f32, f32),
+    ) -> Option<Ray> {
+        // measured in heights so the circle stays round on a wide picture
+        let u = x * aspect;
+        let r = (u.powi(2) + y.powi(2)).sqrt();
+        if r > 1.0 {
+            return None;
+        }
+        let theta = r * self.fov.to_radians() / 2.0;
+        let phi = y.atan2(u);
+
+        let (forward, right, up) = camera.basis();
+        let direction = forward * theta.cos() + (right * phi.cos() + up * phi.sin()) * theta.sin();
+        Some(Ray::new(camera.position, camera.shifted(direction)))
+    }
+
+    fn name(&self) -> &'static str {
+        "fisheye"
+    }
+
+    fn describe(&self) -> ProjectionDescription {
+        ProjectionDescription::Fisheye(FisheyeDescription {
+            fov: Some(self.fov),
+        })
+    }
+}
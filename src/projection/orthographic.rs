@@ -0,0 +1,48 @@
+use crate::{
+    camera::Camera,
+    projection::Projection,
+    renderer::Ray,
+    scene::description::{OrthographicDescription, ProjectionDescription},
+};
+
+// every ray points straight forwards, so things dont get smaller further away. good for technical
+// drawings
+#[derive(Clone, Copy, Debug)]
+pub struct Orthographic {
+    // how much of the world fits top to bottom, in world units
+    pub height: f32,
+}
+
+impl Orthographic {
+    pub fn new(height: f32) -> Orthographic {
+        Orthographic { height }
+    }
+}
+
+impl Projection for Orthographic {
+    fn generate_ray(
+        &self,
+        camera: &Camera,
+        x: f32,
+        y: f32,
+        aspect: f32,
+        _: (f32, f32),
+    ) -> Option<Ray> {
+        let half_height = self.height / 2.0;
+        let half_width = half_height * aspect;
+        let (forward, right, up) = camera.basis();
+        let origin = camera.position + right * (x * half_width) + up * (y * half_height);
+        // stereo eyes slant the rays in so they cross at the convergence distance
+        Some(Ray::new(origin, camera.shifted(forward)))
+    }
+
+    fn name(&self) -> &'static str {
+        "orthographic"
+    }
+
+    fn describe(&self) -> ProjectionDescription {
+        ProjectionDescription::Orthographic(OrthographicDescription {
+            height: Some(self.height),
+        })
+    }
+}
@@ -0,0 +1,50 @@
+use crate::{
+    camera::Camera, common_maths::maths, projection::Projection, renderer::Ray,
+    scene::description::ProjectionDescription,
+};
+
+// the normal kind of camera, uses the field of view and the lens settings on the camera
+#[derive(Clone, Copy, Debug)]
+pub struct Perspective {}
+
+impl Projection for Perspective {
+    fn generate_ray(
+        &self,
+        camera: &Camera,
+        x: f32,
+        y: f32,
+        aspect: f32,
+        lens: (f32, f32),
+    ) -> Option<Ray> {
+        let half_height = (camera.vertical_fov.to_radians() / 2.0).tan();
+        let half_width = half_height * aspect;
+
+        let (forward, right, up) = camera.basis();
+        let (shift_x, shift_y) = camera.frustum_shift;
+        let pixel_direction =
+            forward + right * (x * half_width + shift_x) + up * (y * half_height + shift_y);
+
+        if camera.aperture_radius <= 0.0 {
+            return Some(Ray::new(camera.position, pixel_direction));
+        }
+
+        // thin lens: every ray through this pixel meets at the same spot on the focus plane, no
+        // matter where on the lens it started
+        let (lens_x, lens_y) = if camera.aperture_blades >= 3 {
+            maths::sample_polygon(lens.0, lens.1, camera.aperture_blades)
+        } else {
+            maths::sample_disk(lens.0, lens.1)
+        };
+        let focus_point = camera.position + pixel_direction * camera.focus_distance;
+        let lens_point = camera.position + (right * lens_x + up * lens_y) * camera.aperture_radius;
+        Some(Ray::new(lens_point, focus_point - lens_point))
+    }
+
+    fn name(&self) -> &'static str {
+        "perspective"
+    }
+
+    fn describe(&self) -> ProjectionDescription {
+        ProjectionDescription::Perspective
+    }
+}
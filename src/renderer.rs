@@ -1,6 +1,6 @@
 use std::cmp::Ordering;
 
-use eframe::egui::Rgba;
+use epaint::Rgba;
 use nalgebra::{self, Vector3};
 
 use crate::intersect::{Intersect, Intersection};
@@ -71,7 +71,7 @@ pub fn many_spheres(x: f32, y: f32) -> Rgba {
         }
         return Ordering::Greater;
     });
-    if let Some(_) = unsorted[0].normal {
+    if unsorted[0].normal.is_some() {
         println!("{:?}", unsorted[0].normal);
         return unsorted[0].colour;
     } else {
@@ -84,6 +84,8 @@ pub fn many_spheres(x: f32, y: f32) -> Rgba {
 pub struct Ray {
     pub origin: Vector3<f32>,
     pub direction: Vector3<f32>,
+    // when during the shutter the ray was fired, anything that moves gets put where it was then
+    pub time: f32,
 }
 
 impl Ray {
@@ -91,11 +93,20 @@ impl Ray {
         Ray {
             direction: direction / direction.norm(),
             origin,
+            time: 0.0,
         }
     }
 
     pub fn new_preserve(origin: Vector3<f32>, direction: Vector3<f32>) -> Ray {
-        Ray { direction, origin }
+        Ray {
+            direction,
+            origin,
+            time: 0.0,
+        }
+    }
+
+    pub fn at_time(self, time: f32) -> Ray {
+        Ray { time, ..self }
     }
 
     pub fn at_point(&self, t: f32) -> Vector3<f32> {
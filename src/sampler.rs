@@ -0,0 +1,168 @@
+use std::fmt::Debug;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+pub mod halton;
+pub mod independent;
+pub mod pcg;
+pub mod sobol;
+pub mod stratified;
+
+// everything that needs a random number while rendering gets it from one of these, so that the
+// numbers can be made to cover the space nicely instead of being plain white noise
+pub trait Sampler: Send + Sync + Debug {
+    // called before each camera sample, resets which dimension we are up to
+    fn start_pixel_sample(&mut self, pixel: (u32, u32), sample_index: u32);
+    fn get_1d(&mut self) -> f32;
+    fn get_2d(&mut self) -> (f32, f32);
+    fn samples_per_pixel(&self) -> u32;
+    // every pixel gets its own copy so the threads dont fight over it
+    fn clone_box(&self) -> Box<dyn Sampler>;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SamplerKind {
+    Independent,
+    Stratified,
+    Halton,
+    Sobol,
+}
+
+impl SamplerKind {
+    pub const ALL: [SamplerKind; 4] = [
+        SamplerKind::Independent,
+        SamplerKind::Stratified,
+        SamplerKind::Halton,
+        SamplerKind::Sobol,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SamplerKind::Independent => "independent",
+            SamplerKind::Stratified => "stratified",
+            SamplerKind::Halton => "halton",
+            SamplerKind::Sobol => "sobol",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<SamplerKind> {
+        SamplerKind::ALL
+            .into_iter()
+            .find(|kind| kind.name() == name.to_lowercase())
+    }
+
+    // the same seed, settings and scene always make exactly the same image
+    pub fn build(&self, samples_per_pixel: u32, seed: u64) -> Box<dyn Sampler> {
+        match self {
+            SamplerKind::Independent => {
+                Box::new(independent::Independent::new(samples_per_pixel, seed))
+            }
+            SamplerKind::Stratified => {
+                Box::new(stratified::Stratified::new(samples_per_pixel, seed))
+            }
+            SamplerKind::Halton => Box::new(halton::Halton::new(samples_per_pixel, seed)),
+            SamplerKind::Sobol => Box::new(sobol::Sobol::new(samples_per_pixel, seed)),
+        }
+    }
+}
+
+// largest float below 1, so that nothing ever rounds up to exactly 1
+pub const ONE_MINUS_EPSILON: f32 = 0.99999994;
+
+// murmur style finaliser, scrambles the bits of a number so nearby inputs end up far apart
+pub fn mix_bits(mut v: u64) -> u64 {
+    v ^= v >> 31;
+    v = v.wrapping_mul(0x7fb5d329728ea185);
+    v ^= v >> 27;
+    v = v.wrapping_mul(0x81dadef4bc2dd44d);
+    v ^= v >> 33;
+    v
+}
+
+pub fn hash(values: &[u64]) -> u64 {
+    values
+        .iter()
+        .fold(0x9e3779b97f4a7c15, |acc, v| mix_bits(acc ^ mix_bits(*v)))
+}
+
+// turns the top 24 bits into a float in [0, 1)
+pub fn bits_to_float(bits: u64) -> f32 {
+    (bits >> 40) as f32 / (1u64 << 24) as f32
+}
+
+// the i-th element of a random permutation of 0..length, picked by the seed
+// https://graphics.pixar.com/library/MultiJitteredSampling/paper.pdf
+pub fn permutation_element(mut i: u32, length: u32, seed: u32) -> u32 {
+    let mut w = length - 1;
+    w |= w >> 1;
+    w |= w >> 2;
+    w |= w >> 4;
+    w |= w >> 8;
+    w |= w >> 16;
+    loop {
+        i ^= seed;
+        i = i.wrapping_mul(0xe170893d);
+        i ^= seed >> 16;
+        i ^= (i & w) >> 4;
+        i ^= seed >> 8;
+        i = i.wrapping_mul(0x0929eb3f);
+        i ^= seed >> 23;
+        i ^= (i & w) >> 1;
+        i = i.wrapping_mul(1 | seed >> 27);
+        i = i.wrapping_mul(0x6935fa69);
+        i ^= (i & w) >> 11;
+        i = i.wrapping_mul(0x74dcb303);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0x9e501cc3);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0xc860a3df);
+        i &= w;
+        i ^= i >> 5;
+        if i < length {
+            break;
+        }
+    }
+    i.wrapping_add(seed) % length
+}
+
+#[cfg(test)]
+mod test {
+    use super::{permutation_element, SamplerKind};
+
+    #[test]
+    fn samples_in_unit_square() {
+        for kind in SamplerKind::ALL {
+            let mut sampler = kind.build(16, 1);
+            for index in 0..16 {
+                sampler.start_pixel_sample((3, 7), index);
+                for _ in 0..50 {
+                    let (u, v) = sampler.get_2d();
+                    let w = sampler.get_1d();
+                    for value in [u, v, w] {
+                        assert!((0.0..1.0).contains(&value), "{:?} gave {}", kind, value);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn no_samples_still_means_one() {
+        for kind in SamplerKind::ALL {
+            assert_eq!(kind.build(0, 1).samples_per_pixel(), 1, "{:?}", kind);
+        }
+    }
+
+    #[test]
+    fn permutation_is_a_permutation() {
+        for length in [1, 2, 7, 16, 100] {
+            let mut seen = (0..length)
+                .map(|i| permutation_element(i, length, 0xdeadbeef))
+                .collect::<Vec<u32>>();
+            seen.sort();
+            assert_eq!(seen, (0..length).collect::<Vec<u32>>());
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use crate::sampler::{bits_to_float, hash, Sampler, ONE_MINUS_EPSILON};
+
+const PRIMES: [u32; 64] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+    101, 103, 107, 109, 113, 127, 131, 137, 139, 149, 151, 157, 163, 167, 173, 179, 181, 191, 193,
+    197, 199, 211, 223, 227, 229, 233, 239, 241, 251, 257, 263, 269, 271, 277, 281, 283, 293, 307,
+    311,
+];
+
+// each dimension counts through the digits of the sample index backwards in its own prime base.
+// every pixel gets the whole sequence shifted by a random amount (cranley-patterson rotation) so
+// neighbouring pixels dont make the same pattern
+#[derive(Clone, Debug)]
+pub struct Halton {
+    samples_per_pixel: u32,
+    seed: u64,
+    pixel: (u32, u32),
+    sample_index: u32,
+    dimension: u32,
+}
+
+impl Halton {
+    pub fn new(samples_per_pixel: u32, seed: u64) -> Halton {
+        Halton {
+            samples_per_pixel: samples_per_pixel.max(1),
+            seed,
+            pixel: (0, 0),
+            sample_index: 0,
+            dimension: 0,
+        }
+    }
+
+    fn sample_dimension(&self, dimension: u32) -> f32 {
+        let h = hash(&[
+            self.pixel.0 as u64,
+            self.pixel.1 as u64,
+            dimension as u64,
+            self.seed,
+        ]);
+        if dimension as usize >= PRIMES.len() {
+            // ran out of bases, past this point the sequence is too correlated to be worth it
+            return bits_to_float(hash(&[h, self.sample_index as u64]));
+        }
+        let value = radical_inverse(PRIMES[dimension as usize], self.sample_index);
+        (value + bits_to_float(h)).fract().min(ONE_MINUS_EPSILON)
+    }
+}
+
+// mirrors the digits of the index around the decimal point, in the given base
+pub fn radical_inverse(base: u32, mut index: u32) -> f32 {
+    let inverse_base = 1.0 / base as f64;
+    let mut reversed = 0u64;
+    let mut inverse_base_n = 1.0;
+    while index > 0 {
+        let next = index / base;
+        let digit = index - next * base;
+        reversed = reversed * base as u64 + digit as u64;
+        inverse_base_n *= inverse_base;
+        index = next;
+    }
+    ((reversed as f64 * inverse_base_n) as f32).min(ONE_MINUS_EPSILON)
+}
+
+impl Sampler for Halton {
+    fn start_pixel_sample(&mut self, pixel: (u32, u32), sample_index: u32) {
+        self.pixel = pixel;
+        self.sample_index = sample_index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f32 {
+        let value = self.sample_dimension(self.dimension);
+        self.dimension += 1;
+        value
+    }
+
+    fn get_2d(&mut self) -> (f32, f32) {
+        let value = (
+            self.sample_dimension(self.dimension),
+            self.sample_dimension(self.dimension + 1),
+        );
+        self.dimension += 2;
+        value
+    }
+
+    fn samples_per_pixel(&self) -> u32 {
+        self.samples_per_pixel
+    }
+
+    fn clone_box(&self) -> Box<dyn Sampler> {
+        Box::new(self.clone())
+    }
+}
@@ -0,0 +1,46 @@
+use crate::sampler::{hash, pcg::Pcg32, Sampler};
+
+// plain white noise, every number is picked on its own
+#[derive(Clone, Debug)]
+pub struct Independent {
+    samples_per_pixel: u32,
+    seed: u64,
+    rng: Pcg32,
+}
+
+impl Independent {
+    pub fn new(samples_per_pixel: u32, seed: u64) -> Independent {
+        Independent {
+            samples_per_pixel: samples_per_pixel.max(1),
+            seed,
+            rng: Pcg32::new(seed, 0),
+        }
+    }
+}
+
+impl Sampler for Independent {
+    fn start_pixel_sample(&mut self, pixel: (u32, u32), sample_index: u32) {
+        // the generator is restarted from the pixel and sample, so it doesnt matter which thread
+        // gets to it first
+        self.rng = Pcg32::new(
+            hash(&[pixel.0 as u64, pixel.1 as u64, self.seed]),
+            sample_index as u64,
+        );
+    }
+
+    fn get_1d(&mut self) -> f32 {
+        self.rng.next_f32()
+    }
+
+    fn get_2d(&mut self) -> (f32, f32) {
+        (self.rng.next_f32(), self.rng.next_f32())
+    }
+
+    fn samples_per_pixel(&self) -> u32 {
+        self.samples_per_pixel
+    }
+
+    fn clone_box(&self) -> Box<dyn Sampler> {
+        Box::new(self.clone())
+    }
+}
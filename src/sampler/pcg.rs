@@ -0,0 +1,36 @@
+// tiny pcg32 generator, https://www.pcg-random.org/
+// rand's generators are allowed to change between versions, this one isnt, so a seed always
+// gives the same picture
+#[derive(Clone, Debug)]
+pub struct Pcg32 {
+    state: u64,
+    increment: u64,
+}
+
+const MULTIPLIER: u64 = 0x5851f42d4c957f2d;
+
+impl Pcg32 {
+    pub fn new(seed: u64, sequence: u64) -> Pcg32 {
+        let mut rng = Pcg32 {
+            state: 0,
+            increment: (sequence << 1) | 1,
+        };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+        rng
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old.wrapping_mul(MULTIPLIER).wrapping_add(self.increment);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        let rotation = (old >> 59) as u32;
+        xorshifted.rotate_right(rotation)
+    }
+
+    // uniform in [0, 1)
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+}
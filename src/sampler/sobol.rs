@@ -0,0 +1,111 @@
+use crate::sampler::{hash, mix_bits, permutation_element, Sampler, ONE_MINUS_EPSILON};
+
+// generator matrices for the first two sobol dimensions, the first one is just the bits reversed
+const SOBOL_MATRICES: [[u32; 32]; 2] = sobol_matrices();
+
+const fn sobol_matrices() -> [[u32; 32]; 2] {
+    let mut matrices = [[0; 32]; 2];
+    let mut i = 0;
+    while i < 32 {
+        matrices[0][i] = 1 << (31 - i);
+        matrices[1][i] = if i == 0 {
+            1 << 31
+        } else {
+            matrices[1][i - 1] ^ (matrices[1][i - 1] >> 1)
+        };
+        i += 1;
+    }
+    matrices
+}
+
+// owen scrambled sobol points. only the first two dimensions of the sequence are used, every pair
+// of dimensions we hand out gets its own shuffled order of samples and its own scramble instead
+// (called padding), which keeps the nice 2d distribution without needing a huge table
+// https://jcgt.org/published/0009/04/01/
+#[derive(Clone, Debug)]
+pub struct Sobol {
+    samples_per_pixel: u32,
+    seed: u64,
+    pixel: (u32, u32),
+    sample_index: u32,
+    dimension: u32,
+}
+
+impl Sobol {
+    pub fn new(samples_per_pixel: u32, seed: u64) -> Sobol {
+        Sobol {
+            samples_per_pixel: samples_per_pixel.max(1),
+            seed,
+            pixel: (0, 0),
+            sample_index: 0,
+            dimension: 0,
+        }
+    }
+
+    fn dimension_hash(&self) -> u64 {
+        hash(&[
+            self.pixel.0 as u64,
+            self.pixel.1 as u64,
+            self.dimension as u64,
+            self.seed,
+        ])
+    }
+}
+
+fn sobol_sample(mut index: u32, dimension: usize, scramble_seed: u32) -> f32 {
+    let mut value = 0;
+    let mut bit = 0;
+    while index != 0 {
+        if index & 1 == 1 {
+            value ^= SOBOL_MATRICES[dimension][bit];
+        }
+        index >>= 1;
+        bit += 1;
+    }
+    let value = owen_scramble(value, scramble_seed);
+    (value as f32 * (1.0 / 4294967296.0)).min(ONE_MINUS_EPSILON)
+}
+
+// randomly flips the bits so that each flip depends on all of the bits above it
+fn owen_scramble(mut v: u32, seed: u32) -> u32 {
+    v = v.reverse_bits();
+    v ^= v.wrapping_mul(0x3d20adea);
+    v = v.wrapping_add(seed);
+    v = v.wrapping_mul((seed >> 16) | 1);
+    v ^= v.wrapping_mul(0x05526c56);
+    v ^= v.wrapping_mul(0x53a22864);
+    v.reverse_bits()
+}
+
+impl Sampler for Sobol {
+    fn start_pixel_sample(&mut self, pixel: (u32, u32), sample_index: u32) {
+        self.pixel = pixel;
+        self.sample_index = sample_index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f32 {
+        let h = self.dimension_hash();
+        self.dimension += 1;
+        let index = permutation_element(self.sample_index, self.samples_per_pixel, h as u32);
+        sobol_sample(index, 0, mix_bits(h) as u32)
+    }
+
+    fn get_2d(&mut self) -> (f32, f32) {
+        let h = self.dimension_hash();
+        self.dimension += 2;
+        let index = permutation_element(self.sample_index, self.samples_per_pixel, h as u32);
+        (
+            sobol_sample(index, 0, mix_bits(h) as u32),
+            sobol_sample(index, 1, mix_bits(h >> 32) as u32),
+        )
+    }
+
+    fn samples_per_pixel(&self) -> u32 {
+        self.samples_per_pixel
+    }
+
+    fn clone_box(&self) -> Box<dyn Sampler> {
+        Box::new(self.clone())
+    }
+}
@@ -0,0 +1,83 @@
+use crate::sampler::{hash, pcg::Pcg32, permutation_element, Sampler, ONE_MINUS_EPSILON};
+
+// splits each dimension into one box per sample and jitters a point inside each box. the order
+// the boxes get visited in is shuffled per pixel and per dimension so the dimensions dont line up
+#[derive(Clone, Debug)]
+pub struct Stratified {
+    x_samples: u32,
+    y_samples: u32,
+    seed: u64,
+    rng: Pcg32,
+    pixel: (u32, u32),
+    sample_index: u32,
+    dimension: u32,
+}
+
+impl Stratified {
+    pub fn new(samples_per_pixel: u32, seed: u64) -> Stratified {
+        let samples_per_pixel = samples_per_pixel.max(1);
+        // find the most square grid that fits the sample count exactly
+        let mut x_samples = (samples_per_pixel as f32).sqrt() as u32;
+        while !samples_per_pixel.is_multiple_of(x_samples) {
+            x_samples -= 1;
+        }
+        Stratified {
+            x_samples,
+            y_samples: samples_per_pixel / x_samples,
+            seed,
+            rng: Pcg32::new(seed, 0),
+            pixel: (0, 0),
+            sample_index: 0,
+            dimension: 0,
+        }
+    }
+
+    fn stratum(&mut self) -> u32 {
+        let h = hash(&[
+            self.pixel.0 as u64,
+            self.pixel.1 as u64,
+            self.dimension as u64,
+            self.seed,
+        ]);
+        permutation_element(self.sample_index, self.samples_per_pixel(), h as u32)
+    }
+}
+
+impl Sampler for Stratified {
+    fn start_pixel_sample(&mut self, pixel: (u32, u32), sample_index: u32) {
+        self.pixel = pixel;
+        self.sample_index = sample_index;
+        self.dimension = 0;
+        self.rng = Pcg32::new(
+            hash(&[pixel.0 as u64, pixel.1 as u64, self.seed]),
+            sample_index as u64,
+        );
+    }
+
+    fn get_1d(&mut self) -> f32 {
+        let stratum = self.stratum();
+        self.dimension += 1;
+        let jitter = self.rng.next_f32();
+        ((stratum as f32 + jitter) / self.samples_per_pixel() as f32).min(ONE_MINUS_EPSILON)
+    }
+
+    fn get_2d(&mut self) -> (f32, f32) {
+        let stratum = self.stratum();
+        self.dimension += 2;
+        let x = stratum % self.x_samples;
+        let y = stratum / self.x_samples;
+        let (dx, dy) = (self.rng.next_f32(), self.rng.next_f32());
+        (
+            ((x as f32 + dx) / self.x_samples as f32).min(ONE_MINUS_EPSILON),
+            ((y as f32 + dy) / self.y_samples as f32).min(ONE_MINUS_EPSILON),
+        )
+    }
+
+    fn samples_per_pixel(&self) -> u32 {
+        self.x_samples * self.y_samples
+    }
+
+    fn clone_box(&self) -> Box<dyn Sampler> {
+        Box::new(self.clone())
+    }
+}
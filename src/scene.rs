@@ -1,45 +1,185 @@
-use std::{fs, sync::Arc};
+use std::{collections::BTreeMap, sync::Arc};
 
-use eframe::egui::Rgba;
+use epaint::Rgba;
 use nalgebra::Vector3;
-use serde_json::Value;
 
 use crate::{
+    animation::Animation,
+    camera::Camera,
     intersect::{Intersect, Intersection, TestIntersectionResult},
-    objects::{quad::Quad, sphere::Sphere, triangle::Triangle, *},
+    objects::*,
     renderer::Ray,
+    sampler::Sampler,
+    scene::{description::IncludeDescription, error::Problem},
+    settings::{IntegratorKind, RenderSettings},
     surfaces::{
         diffuse::{self, Diffuse},
         specular::Specular,
-        Surface,
     },
 };
 
+pub mod csv;
+pub mod description;
+pub mod error;
+pub mod gltf;
+pub mod json;
+pub mod materials;
+pub mod mesh;
+pub mod obj;
+pub mod pbrt;
+pub mod ply;
+#[cfg(test)]
+mod temp_folder;
+pub mod watch;
+
+pub use error::SceneError;
+
 #[derive(Clone, Debug)]
 pub struct Scene {
     objects: Vec<Arc<dyn Intersect>>,
+    // what each object was called in the scene file, if anything
+    names: Vec<Option<String>>,
+    // which objects came from one of the includes, these dont get written out with the rest
+    included: Vec<bool>,
     max_depth: u8,
+    // the cameras the scene file set up, in the order they were written
+    pub cameras: Vec<NamedCamera>,
+    // which of the cameras to use when nobody says otherwise, the first one if this is None
+    pub default_camera: Option<String>,
+    pub settings: RenderSettings,
+    // what rays that miss everything see
+    pub background: Rgba,
+    // the named surfaces items can share, including any from import_materials
+    pub materials: BTreeMap<String, materials::Material>,
+    // material libraries the scene file pulled in, as written in it
+    pub import_materials: Vec<String>,
+    // other scene files the scene file pulled items in from, as written in it
+    pub includes: Vec<IncludeDescription>,
+    // things in the file that were left out or done differently, but didnt stop it loading
+    pub warnings: Vec<Problem>,
+    // every file that went into the scene, the scene file first, so the viewer can reload it when
+    // any of them change
+    pub sources: Vec<String>,
+    // keyframes from the scene file, if it has any. this scene is how it looks with none of them
+    // applied
+    pub animation: Option<Arc<Animation>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct NamedCamera {
+    pub name: String,
+    pub camera: Camera,
 }
 
 // max number of bounces
 const DEPTH: u8 = 4;
 
 impl Scene {
-    #[allow(dead_code)]
-    pub fn test_intersections(&self, ray: Ray, current_depth: u8) -> TestIntersectionResult {
-        // go over each object in the scene
-        //println!("{}", current_depth);
-        let all_objects = self
-            .objects
+    // an empty scene to build up in code, max_depth is how many times rays bounce when the
+    // settings dont say
+    pub fn new(max_depth: u8) -> Scene {
+        Scene::from_objects(vec![], max_depth)
+    }
+
+    pub fn from_objects(objects: Vec<Arc<dyn Intersect>>, max_depth: u8) -> Scene {
+        Scene {
+            names: vec![None; objects.len()],
+            included: vec![false; objects.len()],
+            objects,
+            max_depth,
+            cameras: vec![],
+            default_camera: None,
+            settings: RenderSettings::default(),
+            background: Rgba::BLACK,
+            materials: BTreeMap::new(),
+            import_materials: vec![],
+            includes: vec![],
+            warnings: vec![],
+            sources: vec![],
+            animation: None,
+        }
+    }
+
+    // picks how to load the file from its extension, json if it isnt one of the others. ply point
+    // clouds need from_ply to say how big the points are
+    pub fn from_file(file_name: &str) -> Result<Scene, SceneError> {
+        let extension = std::path::Path::new(file_name)
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase());
+        match extension.as_deref() {
+            Some("csv") => Scene::from_csv(file_name),
+            Some("gltf") | Some("glb") => Scene::from_gltf(file_name),
+            Some("obj") => Scene::from_obj(file_name),
+            Some("ply") => Scene::from_ply(file_name, None),
+            Some("pbrt") => Scene::from_pbrt(file_name),
+            _ => Scene::from_json(file_name),
+        }
+    }
+
+    pub fn add(&mut self, object: Arc<dyn Intersect>) {
+        self.add_named(None, object);
+    }
+
+    pub fn add_named(&mut self, name: Option<String>, object: Arc<dyn Intersect>) {
+        self.objects.push(object);
+        self.names.push(name);
+        self.included.push(false);
+    }
+
+    pub fn objects(&self) -> &[Arc<dyn Intersect>] {
+        &self.objects
+    }
+
+    pub fn default_camera(&self) -> Option<&Camera> {
+        let named = match &self.default_camera {
+            Some(name) => self.camera(name),
+            None => None,
+        };
+        named.or(self.cameras.first().map(|named| &named.camera))
+    }
+
+    pub fn camera(&self, name: &str) -> Option<&Camera> {
+        self.cameras
             .iter()
-            // go over each object in the scene and find the intersections
-            .map(|obj| obj.test_intersection(&ray, Rgba::from_gray(1.0)))
-            .collect::<Vec<TestIntersectionResult>>();
+            .find(|named| named.name == name)
+            .map(|named| &named.camera)
+    }
 
-        let mut intersect = all_objects.clone().into_iter().min().unwrap(); // if the normal is a value, it implies that something has been hit
+    // works out the colour for a ray leaving the camera
+    pub fn integrate(
+        &self,
+        ray: Ray,
+        settings: &RenderSettings,
+        sampler: &mut dyn Sampler,
+    ) -> Rgba {
+        match settings.integrator {
+            IntegratorKind::Recursive => {
+                let max_depth = settings.max_depth.unwrap_or(self.max_depth);
+                self.test_intersections(ray, 0, max_depth, sampler).0.colour
+            }
+            IntegratorKind::Normals => match self.closest_hit(&ray).0.normal {
+                Some(normal) => Rgba::from_rgb(
+                    0.5 + 0.5 * normal.direction.x,
+                    0.5 + 0.5 * normal.direction.y,
+                    0.5 + 0.5 * normal.direction.z,
+                ),
+                None => Rgba::BLACK,
+            },
+        }
+    }
+
+    pub fn test_intersections(
+        &self,
+        ray: Ray,
+        current_depth: u8,
+        max_depth: u8,
+        sampler: &mut dyn Sampler,
+    ) -> TestIntersectionResult {
+        //println!("{}", current_depth);
+        let mut intersect = self.closest_hit(&ray); // if the normal is a value, it implies that something has been hit
 
         if let Some(normal_ray) = intersect.0.normal {
-            if current_depth < self.max_depth {
+            if current_depth < max_depth {
                 //aiming to improve this so that the surfaces can give rays to render and recieve the information
 
                 // do another bounce if theres still bounces avaliable
@@ -47,9 +187,17 @@ impl Scene {
                     .clone()
                     .1
                     .unwrap()
-                    .request_rays(&normal_ray, &ray)
+                    .request_rays(&normal_ray, &ray, sampler)
                     .into_iter()
-                    .map(|r| self.test_intersections(r, current_depth + 1))
+                    // bounces happen at the same moment as the ray that caused them
+                    .map(|r| {
+                        self.test_intersections(
+                            r.at_time(ray.time),
+                            current_depth + 1,
+                            max_depth,
+                            sampler,
+                        )
+                    })
                     .collect::<Vec<TestIntersectionResult>>();
 
                 intersect.0.colour = intersect
@@ -64,6 +212,31 @@ impl Scene {
         return intersect;
     }
 
+    // the first thing the ray runs into, without bouncing
+    pub fn closest_hit(&self, ray: &Ray) -> TestIntersectionResult {
+        let closest = self
+            .objects
+            .iter()
+            // go over each object in the scene and find the intersections
+            .map(|obj| obj.test_intersection(ray, Rgba::from_gray(1.0)))
+            .min();
+        match closest {
+            Some(hit) if hit.0.distance.is_some() => hit.shaded(),
+            _ => TestIntersectionResult(Intersection::new(self.background, None, None), None),
+        }
+    }
+
+    // which object the ray runs into first, for clicking on things in the viewer
+    pub fn pick(&self, ray: &Ray) -> Option<usize> {
+        self.objects
+            .iter()
+            .map(|object| object.test_intersection(ray, Rgba::from_gray(1.0)))
+            .enumerate()
+            .filter(|(_, hit)| hit.0.distance.is_some())
+            .min_by(|(_, a), (_, b)| a.cmp(b))
+            .map(|(index, _)| index)
+    }
+
     #[allow(dead_code)]
     pub fn test_intersections_vec(&self, ray: Ray) -> Intersection {
         // this is being kept around because if i get smart, maybe it will come in handy to give me more control
@@ -141,10 +314,7 @@ impl Scene {
             1.0,
         )));
 
-        Scene {
-            objects,
-            max_depth: DEPTH,
-        }
+        Scene::from_objects(objects, DEPTH)
     }
 
     #[allow(dead_code)]
@@ -193,13 +363,10 @@ impl Scene {
             Arc::new(sphere::Sphere::blank_specular_surface(d, 0.1)),
             Arc::new(sphere::Sphere::blank_specular_surface(e, 0.1)),
             Arc::new(sphere::Sphere::blank_specular_surface(f, 0.1)),
-            Arc::new(world_light::WorldLight {}),
+            Arc::new(world_light::WorldLight::default()),
             Arc::new(quad),
         ];
-        Scene {
-            objects,
-            max_depth: DEPTH,
-        }
+        Scene::from_objects(objects, DEPTH)
     }
 
     #[allow(dead_code)]
@@ -217,164 +384,9 @@ impl Scene {
                 nalgebra::Vector3::new(3.0, 0.0, 0.0),
                 1.0,
             )),
-            Arc::new(world_light::WorldLight {}),
+            Arc::new(world_light::WorldLight::default()),
         ];
-        Scene {
-            objects,
-            max_depth: DEPTH,
-        }
-    }
-
-    #[allow(dead_code)]
-    pub fn from_csv(path: String) -> Scene {
-        let mut objects: Vec<Arc<dyn Intersect>> = vec![];
-
-        let make_sphere = |mut input: Vec<&str>| {
-            input.remove(0);
-            let numbers = input
-                .into_iter()
-                .map(|val: &str| val.parse::<f32>().unwrap())
-                .collect::<Vec<f32>>();
-
-            let object = sphere::Sphere::blank_specular_surface(
-                Vector3::new(numbers[0], numbers[1], numbers[2]),
-                numbers[3],
-            );
-            return Arc::new(object);
-        };
-
-        let make_light = |mut input: Vec<&str>| {
-            input.remove(0);
-            let numbers = input
-                .into_iter()
-                .map(|val: &str| val.parse::<f32>().expect(&format!("yikes! {}", val)))
-                .collect::<Vec<f32>>();
-
-            let origin = Vector3::new(numbers[0], numbers[1], numbers[2]);
-            let object = light::PointLight::new(origin, numbers[3]);
-            return Arc::new(object);
-        };
-
-        let make_triangle = |mut input: Vec<&str>| {
-            input.remove(0);
-            let numbers = input
-                .into_iter()
-                .map(|val: &str| val.parse::<f32>().unwrap())
-                .collect::<Vec<f32>>();
-
-            let a = Vector3::new(numbers[0], numbers[1], numbers[2]);
-            let b = Vector3::new(numbers[3], numbers[4], numbers[5]);
-            let c = Vector3::new(numbers[6], numbers[7], numbers[8]);
-            let colour = Rgba::from_rgb(numbers[9], numbers[10], numbers[11]);
-            let tri = triangle::Triangle::from_3_points(&a, &b, &c, colour);
-            return Arc::new(tri);
-        };
-
-        let lines = std::fs::read_to_string(path).unwrap();
-        for line in lines.lines() {
-            let data = line.split(",").collect::<Vec<&str>>();
-            match data[0] {
-                "s" => objects.push(make_sphere(data)),
-                "l" => objects.push(make_light(data)),
-                "t" => objects.push(make_triangle(data)),
-                _ => println!("bad input: {:?}", line),
-            }
-        }
-        Scene {
-            objects,
-            max_depth: DEPTH,
-        }
-    }
-
-    pub fn from_json(file_name: &str) -> Scene {
-        let file_contents = String::from_utf8(fs::read(file_name).unwrap()).unwrap();
-        let contents_objects: Value = serde_json::from_str(&file_contents).unwrap();
-        let a = contents_objects["items"].as_array().unwrap();
-        let mut objects: Vec<Arc<dyn Intersect>> = vec![];
-        for item in a {
-            match item["kind"].as_str() {
-                Some("sphere") => {
-                    objects.push(Arc::new(Scene::parse_sphere(&item)));
-                }
-                Some("triangle") => {
-                    objects.push(Arc::new(Scene::parse_triangle(&item)));
-                }
-                Some("quad") => {
-                    objects.push(Arc::new(Scene::parse_quad(&item)));
-                }
-                None => {
-                    panic!("kind not specified")
-                }
-                Some(_) => {
-                    panic!("invalid kind of object")
-                }
-            }
-        }
-        Scene {
-            objects,
-            max_depth: 1,
-        }
-    }
-
-    fn parse_diffuse(data: &Value) -> Arc<dyn Surface> {
-        let colour = Rgba::from_rgb(
-            data["colour"][0].as_f64().unwrap() as f32,
-            data["colour"][1].as_f64().unwrap() as f32,
-            data["colour"][2].as_f64().unwrap() as f32,
-        );
-        Arc::new(Diffuse::new(
-            colour,
-            data["samples"].as_u64().unwrap() as usize,
-        ))
-    }
-
-    fn parse_specular(data: &Value) -> Arc<dyn Surface> {
-        let colour = Rgba::from_rgb(
-            data["colour"][0].as_f64().unwrap() as f32,
-            data["colour"][1].as_f64().unwrap() as f32,
-            data["colour"][2].as_f64().unwrap() as f32,
-        );
-        Arc::new(Specular::with_colour(colour))
-    }
-
-    fn parse_surface(data: &Value) -> Arc<dyn Surface> {
-        match data["type"].as_str() {
-            Some("specular") => Scene::parse_specular(&data),
-            Some("diffuse") => Scene::parse_diffuse(&data),
-            None => Arc::new(Specular::new()),
-            Some(_) => panic!("invalid surface type"),
-        }
-    }
-
-    fn parse_sphere(data: &Value) -> Sphere {
-        let origin = Scene::parse_vec3(&data["origin"]);
-        let radius = data["radius"].as_f64().unwrap() as f32;
-        let surface = Scene::parse_surface(&data["surface"]);
-        return Sphere::with_shader(origin, radius, surface);
-    }
-
-    fn parse_triangle(data: &Value) -> Triangle {
-        let a = Scene::parse_vec3(&data["a"]);
-        let b = Scene::parse_vec3(&data["b"]);
-        let c = Scene::parse_vec3(&data["c"]);
-        let surface = Scene::parse_surface(&data["surface"]);
-        return Triangle::from_3_points_and_surface(&a, &b, &c, surface);
-    }
-
-    fn parse_quad(data: &Value) -> Quad {
-        let a = Scene::parse_vec3(&data["a"]);
-        let b = Scene::parse_vec3(&data["b"]);
-        let c = Scene::parse_vec3(&data["c"]);
-        let surface = Scene::parse_surface(&data["surface"]);
-        return Quad::from_3_points_and_surface(&a, &b, &c, surface);
-    }
-
-    fn parse_vec3(data: &Value) -> Vector3<f32> {
-        Vector3::new(
-            data[0].as_f64().unwrap() as f32,
-            data[1].as_f64().unwrap() as f32,
-            data[2].as_f64().unwrap() as f32,
-        )
+        Scene::from_objects(objects, DEPTH)
     }
 }
 
@@ -386,9 +398,11 @@ mod test {
     use nalgebra::Vector3;
 
     use crate::{
+        camera::Camera,
         intersect::Intersect,
         objects::{quad::Quad, sphere::Sphere, triangle},
-        scene::Scene,
+        renderer::Ray,
+        scene::{NamedCamera, Scene},
         surfaces::{diffuse::Diffuse, specular::Specular},
     };
 
@@ -420,14 +434,90 @@ mod test {
             Arc::new(Specular::new()),
         )));
 
-        let expected = Scene {
-            objects,
-            max_depth: 1,
-        };
+        let mut expected = Scene::from_objects(objects, 1);
+        expected.settings.max_depth = Some(1);
+        expected.cameras.push(NamedCamera {
+            name: String::from("front"),
+            camera: Camera::look_at(
+                Vector3::new(-3.0, 0.0, 1.0),
+                Vector3::new(-2.0, 0.0, 1.0),
+                600,
+                600,
+            ),
+        });
+
+        assert_eq!(
+            Scene::from_json("jsons/ci.json").unwrap().describe(),
+            expected.describe()
+        );
+    }
+
+    // saving and loading again should give back exactly the same scene
+    #[test]
+    fn scenes_survive_being_saved() {
+        let mut built = Scene::pondering_orbs();
+        built.cameras.push(NamedCamera {
+            name: String::from("side"),
+            camera: Camera::look_at(Vector3::new(0.0, -5.0, 1.0), Vector3::zeros(), 320, 200),
+        });
+        let mut scenes = vec![built];
+        for file in std::fs::read_dir("jsons").unwrap() {
+            let path = file.unwrap().path();
+            if !path.to_str().unwrap().ends_with(".schema.json") {
+                scenes.push(Scene::from_json(path.to_str().unwrap()).unwrap());
+            }
+        }
+        for scene in scenes {
+            let saved = scene.to_json();
+            let loaded =
+                Scene::from_value(&serde_json::from_str(&saved).unwrap(), "saved.json").unwrap();
+            assert_eq!(loaded.to_json(), saved);
+            assert_eq!(loaded.objects.len(), scene.describe().items.len());
+            if let Some(animation) = &loaded.animation {
+                assert!(animation.scene_at(animation.frames.1).is_ok());
+            }
+        }
+
+        // the file as written describes the same scene as the one loaded from it
+        let scene = Scene::from_json("jsons/ci.json").unwrap();
+        let reloaded = Scene::from_value(
+            &serde_json::from_str(&scene.to_json()).unwrap(),
+            "jsons/ci.json",
+        )
+        .unwrap();
+        assert_eq!(reloaded.describe(), scene.describe());
+    }
 
+    #[test]
+    fn built_in_scenes_have_json_versions() {
+        for (file, scene) in [
+            ("jsons/curve.json", Scene::curve()),
+            ("jsons/pondering_orbs.json", Scene::pondering_orbs()),
+            ("jsons/eclipse.json", Scene::eclipse()),
+        ] {
+            assert_eq!(Scene::from_json(file).unwrap().to_json(), scene.to_json());
+        }
+    }
+
+    #[test]
+    fn rays_that_miss_see_the_background() {
+        let scene = Scene::from_value(
+            &serde_json::json!({ "world": { "background": [0.2, 0.4, 0.6] }, "items": [] }),
+            "empty.json",
+        )
+        .unwrap();
+        let ray = Ray::new(Vector3::zeros(), Vector3::new(1.0, 0.0, 0.0));
         assert_eq!(
-            format!("{:?}", expected),
-            format!("{:?}", Scene::from_json("jsons/ci.json"))
+            scene.closest_hit(&ray).0.colour,
+            Rgba::from_rgb(0.2, 0.4, 0.6)
         );
+        assert!(scene.to_json().contains("\"background\": [0.2, 0.4, 0.6]"));
+    }
+
+    #[test]
+    fn motion_blur_scene_loads() {
+        let scene = Scene::from_json("jsons/motion_blur.json").unwrap();
+        assert_eq!(scene.default_camera().unwrap().shutter, (0.0, 1.0));
+        assert_eq!(scene.objects.len(), 2);
     }
 }
@@ -0,0 +1,365 @@
+use std::{fs, sync::Arc};
+
+use nalgebra::Vector3;
+
+use crate::{
+    intersect::Intersect,
+    objects::{light::PointLight, sphere::Sphere, triangle::Triangle},
+    scene::{
+        description::{
+            DiffuseDescription, EmissiveDescription, MetallicRoughnessDescription,
+            SpecularDescription, SurfaceDescription, SURFACE_TYPES,
+        },
+        error::{Location, Problem, Reason, SceneError},
+        Scene, DEPTH,
+    },
+    surfaces::{specular::Specular, Surface},
+};
+
+// how many rays diffuse and rough surfaces send off when a row doesnt say
+const SAMPLES: usize = 3;
+
+impl Scene {
+    // one object per line, the first column says what it is and the rest are numbers:
+    // s,x,y,z,radius  l,x,y,z,intensity  t,ax,ay,az,bx,by,bz,cx,cy,cz
+    // spheres and triangles can have a surface on the end, either just r,g,b (a tinted mirror for
+    // spheres and a diffuse colour for triangles, like the old files have) or a surface type and
+    // its numbers:
+    // specular,r,g,b  diffuse,r,g,b[,samples]  emissive,r,g,b
+    // metallic_roughness,r,g,b,metallic,roughness[,samples]
+    pub fn from_csv(file_name: &str) -> Result<Scene, SceneError> {
+        let bytes = fs::read(file_name).map_err(|error| {
+            SceneError::single(
+                file_name,
+                Location::File,
+                Reason::Unreadable(error.to_string()),
+            )
+        })?;
+        let lines = String::from_utf8(bytes)
+            .map_err(|_| SceneError::single(file_name, Location::File, Reason::NotUtf8))?;
+
+        let mut objects: Vec<Arc<dyn Intersect>> = vec![];
+        let mut problems = vec![];
+        for (number, line) in lines.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let location = Location::Line(number + 1, None);
+            match read_row(line) {
+                Ok(object) => objects.push(object),
+                Err(reasons) => problems.extend(reasons.into_iter().map(|reason| Problem {
+                    location: location.clone(),
+                    reason,
+                })),
+            }
+        }
+
+        if !problems.is_empty() {
+            return Err(SceneError::new(file_name, problems));
+        }
+        let mut scene = Scene::from_objects(objects, DEPTH);
+        scene.sources.push(file_name.to_string());
+        Ok(scene)
+    }
+}
+
+fn read_row(line: &str) -> Result<Arc<dyn Intersect>, Vec<Reason>> {
+    let data = line.split(',').map(str::trim).collect::<Vec<&str>>();
+    // how many numbers each kind needs before its surface
+    let needed = match data[0] {
+        "s" | "l" => 4,
+        "t" => 9,
+        kind => {
+            return Err(vec![Reason::UnknownKind {
+                found: kind.to_string(),
+                expected: vec![String::from("s"), String::from("l"), String::from("t")],
+            }])
+        }
+    };
+    // the surface starts at the first word after the position, if there is one
+    let words = data
+        .iter()
+        .skip(needed + 1)
+        .position(|value| value.parse::<f32>().is_err())
+        .map(|i| i + needed + 1);
+    let (numbers, surface) = match words {
+        Some(i) if data[0] != "l" => (&data[1..i], Some((data[i], &data[i + 1..]))),
+        _ => (&data[1..], None),
+    };
+    let numbers = read_numbers(numbers)?;
+    if numbers.len() < needed {
+        return Err(vec![Reason::BadVectorLength {
+            expected: needed,
+            found: numbers.len(),
+        }]);
+    }
+
+    let surface: Arc<dyn Surface> = match (surface, &numbers[needed..]) {
+        (Some((kind, values)), []) => read_surface(kind, &read_numbers(values)?)?.build(),
+        // lights dont have surfaces, and old files had all sorts after them
+        _ if data[0] == "l" => Arc::new(Specular::new()),
+        (None, []) => Arc::new(Specular::new()),
+        (None, [r, g, b]) => {
+            let colour = [*r, *g, *b];
+            match data[0] {
+                "s" => SurfaceDescription::Specular(SpecularDescription { colour }),
+                _ => SurfaceDescription::Diffuse(DiffuseDescription {
+                    colour,
+                    samples: SAMPLES,
+                }),
+            }
+            .build()
+        }
+        (_, extra) => {
+            return Err(vec![Reason::BadVectorLength {
+                expected: needed + 3,
+                found: needed + extra.len(),
+            }])
+        }
+    };
+
+    let point = |i: usize| Vector3::new(numbers[i], numbers[i + 1], numbers[i + 2]);
+    let object: Arc<dyn Intersect> = match data[0] {
+        "s" => Arc::new(Sphere::with_shader(point(0), numbers[3], surface)),
+        "l" => Arc::new(PointLight::new(point(0), numbers[3])),
+        _ => Arc::new(Triangle::from_3_points_and_surface(
+            &point(0),
+            &point(3),
+            &point(6),
+            surface,
+        )),
+    };
+    Ok(object)
+}
+
+fn read_numbers(values: &[&str]) -> Result<Vec<f32>, Vec<Reason>> {
+    let mut numbers = vec![];
+    let mut reasons = vec![];
+    for value in values {
+        match value.parse::<f32>() {
+            Ok(number) => numbers.push(number),
+            Err(_) => reasons.push(Reason::BadNumber(value.to_string())),
+        }
+    }
+    if !reasons.is_empty() {
+        return Err(reasons);
+    }
+    Ok(numbers)
+}
+
+fn read_surface(kind: &str, numbers: &[f32]) -> Result<SurfaceDescription, Vec<Reason>> {
+    // how many numbers it can have, the last one being the samples if it has them
+    let (fewest, most) = match kind {
+        "specular" | "emissive" => (3, 3),
+        "diffuse" => (3, 4),
+        "metallic_roughness" => (5, 6),
+        _ => {
+            return Err(vec![Reason::UnknownKind {
+                found: kind.to_string(),
+                expected: SURFACE_TYPES.map(String::from).to_vec(),
+            }])
+        }
+    };
+    if numbers.len() < fewest || numbers.len() > most {
+        return Err(vec![match fewest == most {
+            true => Reason::BadVectorLength {
+                expected: fewest,
+                found: numbers.len(),
+            },
+            false => Reason::BadNumberCount {
+                fewest,
+                most,
+                found: numbers.len(),
+            },
+        }]);
+    }
+    let colour = [numbers[0], numbers[1], numbers[2]];
+    let samples = match numbers.len() {
+        n if n == most && most > fewest => {
+            let samples = numbers[most - 1];
+            if samples < 1.0 || samples.fract() != 0.0 {
+                return Err(vec![Reason::Invalid(format!(
+                    "the samples should be a whole number from 1 up, not {}",
+                    samples
+                ))]);
+            }
+            samples as usize
+        }
+        _ => SAMPLES,
+    };
+    Ok(match kind {
+        "specular" => SurfaceDescription::Specular(SpecularDescription { colour }),
+        "emissive" => SurfaceDescription::Emissive(EmissiveDescription { colour }),
+        "diffuse" => SurfaceDescription::Diffuse(DiffuseDescription { colour, samples }),
+        _ => SurfaceDescription::MetallicRoughness(MetallicRoughnessDescription {
+            colour,
+            metallic: numbers[3],
+            roughness: numbers[4],
+            samples,
+        }),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::scene::{
+        description::{
+            DiffuseDescription, EmissiveDescription, ItemDescription, MetallicRoughnessDescription,
+            SpecularDescription, SurfaceDescription, SurfaceRef,
+        },
+        error::{Location, Reason},
+        temp_folder::TempFolder,
+        Scene,
+    };
+
+    #[test]
+    fn csv_problems_have_line_numbers() {
+        let folder = TempFolder::new();
+        let file = folder.write(
+            "broken.csv",
+            "s,1,2,3,0.5\n\nl,3,2,one,1\nt,1,2,3\nq,1,2,3\n",
+        );
+        let error = Scene::from_csv(&file).unwrap_err();
+        let found = error
+            .problems
+            .iter()
+            .map(|problem| (problem.location.clone(), problem.reason.clone()))
+            .collect::<Vec<(Location, Reason)>>();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    Location::Line(3, None),
+                    Reason::BadNumber("one".to_string())
+                ),
+                (
+                    Location::Line(4, None),
+                    Reason::BadVectorLength {
+                        expected: 9,
+                        found: 3
+                    }
+                ),
+                (
+                    Location::Line(5, None),
+                    Reason::UnknownKind {
+                        found: "q".to_string(),
+                        expected: vec!["s".to_string(), "l".to_string(), "t".to_string()]
+                    }
+                ),
+            ]
+        );
+        assert!(Scene::from_csv("start.csv").is_ok());
+    }
+
+    #[test]
+    fn csv_rows_can_have_surfaces() {
+        let folder = TempFolder::new();
+        let file = folder.write(
+            "surfaces.csv",
+            "s,1,2,3,0.4,0.2,0.2,0.2\n\
+             s,1,2,3,0.4,metallic_roughness,1,0,0,0.5,0.25\n\
+             t,0,0,1,1,0,1,0,1,1,0.4,0.8,0.1\n\
+             t,0,0,1,1,0,1,0,1,1,diffuse,0,0,1,8\n\
+             t,0,0,1,1,0,1,0,1,1,emissive,4,4,4\n",
+        );
+        let scene = Scene::from_csv(&file).unwrap();
+        let surfaces = scene
+            .describe()
+            .items
+            .iter()
+            .map(|item| match item {
+                ItemDescription::Sphere(sphere) => sphere.surface.clone(),
+                ItemDescription::Triangle(triangle) => triangle.surface.clone(),
+                other => panic!("expected spheres and triangles, got {:?}", other),
+            })
+            .map(|surface| match surface {
+                Some(SurfaceRef::Surface(surface)) => surface,
+                other => panic!("expected a surface, got {:?}", other),
+            })
+            .collect::<Vec<SurfaceDescription>>();
+        assert_eq!(
+            surfaces,
+            vec![
+                SurfaceDescription::Specular(SpecularDescription {
+                    colour: [0.2, 0.2, 0.2]
+                }),
+                SurfaceDescription::MetallicRoughness(MetallicRoughnessDescription {
+                    colour: [1.0, 0.0, 0.0],
+                    metallic: 0.5,
+                    roughness: 0.25,
+                    samples: 3
+                }),
+                SurfaceDescription::Diffuse(DiffuseDescription {
+                    colour: [0.4, 0.8, 0.1],
+                    samples: 3
+                }),
+                SurfaceDescription::Diffuse(DiffuseDescription {
+                    colour: [0.0, 0.0, 1.0],
+                    samples: 8
+                }),
+                SurfaceDescription::Emissive(EmissiveDescription {
+                    colour: [4.0, 4.0, 4.0]
+                }),
+            ]
+        );
+
+        let file = folder.write(
+            "broken.csv",
+            "s,1,2,3,0.4,shiny,1,1,1\nt,0,0,1,1,0,1,0,1,1,diffuse,1\n\
+             s,1,2,3,0.4,diffuse,1,1,1,1,1\ns,1,2,3,0.4,emissive,1,1\n\
+             s,1,2,3,0.4,diffuse,1,1,1,0\ns,1,2,3,0.4,metallic_roughness,1,1,1,0,0,2.5\n",
+        );
+        let error = Scene::from_csv(&file).unwrap_err();
+        assert!(matches!(
+            error.problems[0].reason,
+            Reason::UnknownKind { .. }
+        ));
+        assert_eq!(
+            error.problems[1].reason,
+            Reason::BadNumberCount {
+                fewest: 3,
+                most: 4,
+                found: 1
+            }
+        );
+        assert_eq!(
+            error.problems[2].reason.to_string(),
+            "should have 3 to 4 numbers but has 5"
+        );
+        assert_eq!(
+            error.problems[3].reason,
+            Reason::BadVectorLength {
+                expected: 3,
+                found: 2
+            }
+        );
+        assert_eq!(
+            error.problems[4..]
+                .iter()
+                .map(|problem| problem.reason.to_string())
+                .collect::<Vec<String>>(),
+            vec![
+                "the samples should be a whole number from 1 up, not 0",
+                "the samples should be a whole number from 1 up, not 2.5",
+            ]
+        );
+        assert_eq!(error.problems[5].location, Location::Line(6, None));
+    }
+
+    #[test]
+    fn csv_files_convert_to_json() {
+        for file in [
+            "start.csv",
+            "many_tris.csv",
+            "blendertest.csv",
+            "blender/test.csv",
+            "blender/monky.csv",
+        ] {
+            let scene = Scene::from_csv(file).unwrap();
+            let json = scene.to_json();
+            let converted =
+                Scene::from_value(&serde_json::from_str(&json).unwrap(), "converted.json").unwrap();
+            assert_eq!(converted.to_json(), json, "{}", file);
+        }
+    }
+}
@@ -0,0 +1,1226 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use epaint::Rgba;
+use nalgebra::{Unit, UnitQuaternion, Vector2, Vector3};
+use schemars::JsonSchema;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    animation::Interpolation,
+    camera::Camera,
+    common_maths::transform::Transform,
+    filter::FilterKind,
+    intersect::Intersect,
+    objects::{
+        light::PointLight,
+        plane::Plane,
+        quad::Quad,
+        sphere::Sphere,
+        transformed::Transformed,
+        triangle::{Shading, Triangle},
+        world_light::WorldLight,
+    },
+    projection::{
+        equirectangular::Equirectangular, fisheye::Fisheye, orthographic::Orthographic,
+        perspective::Perspective, Projection,
+    },
+    sampler::SamplerKind,
+    scene::{
+        error::{Location, Problem, Reason},
+        json::{index, join},
+        materials::Material,
+    },
+    settings::{AdaptiveSettings, IntegratorKind, RenderSettings},
+    stereo::{Stereo, StereoLayout},
+    surfaces::{
+        diffuse::Diffuse, emissive::Emissive, metallic_roughness::MetallicRoughness,
+        specular::Specular, Surface,
+    },
+};
+
+// what a scene file looks like. loading reads one of these and then builds the real objects out of
+// it, saving goes the other way, and the json schema for editors comes straight from these types
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SceneDescription {
+    // where the schema is, so editors can check the file as it is written
+    #[serde(rename = "$schema", default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settings: Option<SettingsDescription>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cameras: Vec<CameraDescription>,
+    // for when there is only one camera
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub camera: Option<CameraDescription>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_camera: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub world: Option<WorldDescription>,
+    // other files to take materials from, relative to this one. later files and this file's own
+    // materials win when two have the same name
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub import_materials: Vec<String>,
+    // surfaces that items can use by name, so a colour used all over only has to be changed once
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub materials: BTreeMap<String, SurfaceDescription>,
+    // other scene files whose items get added to this one, only their items come along
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub includes: Vec<IncludeDescription>,
+    // can only be left out when everything comes from includes
+    #[serde(default)]
+    pub items: Vec<ItemDescription>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub animation: Option<AnimationDescription>,
+}
+
+// another scene file's items, put somewhere in this one
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct IncludeDescription {
+    // relative to the file doing the including
+    pub file: String,
+    // goes on the front of the included items' names, so two copies of a file can be told apart
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    // moves everything in the file together
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transform: Option<TransformDescription>,
+}
+
+// everything about the scene that isnt one of the items
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct WorldDescription {
+    // what rays that miss everything see, black if left out. a world_light covers this up
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub background: Option<[f32; 3]>,
+}
+
+// anything left out stays at the default
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SettingsDescription {
+    // the size of the picture, 600 by 600 if left out. cameras can have their own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sampler: Option<SamplerKind>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub samples_per_pixel: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<FilterKind>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_depth: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub integrator: Option<IntegratorKind>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adaptive: Option<AdaptiveDescription>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AdaptiveDescription {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub noise_threshold: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_samples_per_pixel: Option<u32>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CameraDescription {
+    // "camera 0", "camera 1"... if left out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub position: [f32; 3],
+    pub target: [f32; 3],
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub up: Option<[f32; 3]>,
+    // degrees top to bottom
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fov: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aperture: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focus_distance: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blades: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub projection: Option<ProjectionDescription>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stereo: Option<StereoDescription>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shutter: Option<[f32; 2]>,
+    // only for cameras that are a different size to the one in the settings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+}
+
+// anything left out gets the same default as projection::from_name
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProjectionDescription {
+    Perspective,
+    Orthographic(OrthographicDescription),
+    Fisheye(FisheyeDescription),
+    Equirectangular,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct OrthographicDescription {
+    // world units top to bottom
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<f32>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct FisheyeDescription {
+    // degrees across the whole circle
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fov: Option<f32>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct StereoDescription {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ipd: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub convergence: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layout: Option<StereoLayout>,
+}
+
+// every kind of thing that can go in the items list. the kind field says which one it is
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ItemDescription {
+    Sphere(SphereDescription),
+    Triangle(TriangleDescription),
+    Quad(QuadDescription),
+    Plane(PlaneDescription),
+    PointLight(PointLightDescription),
+    WorldLight(WorldLightDescription),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SphereDescription {
+    // lets animation tracks find the item
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub origin: [f32; 3],
+    pub radius: f32,
+    // how far the centre moves between the shutter opening at time 0 and time 1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub velocity: Option<[f32; 3]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub surface: Option<SurfaceRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transform: Option<TransformDescription>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transform_end: Option<TransformDescription>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct TriangleDescription {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub a: [f32; 3],
+    pub b: [f32; 3],
+    pub c: [f32; 3],
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub surface: Option<SurfaceRef>,
+    // the normal at each corner, for meshes that should look smooth
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub normals: Option<[[f32; 3]; 3]>,
+    // texture coordinates at each corner
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uvs: Option<[[f32; 2]; 3]>,
+    // colours at each corner that get blended across and multiplied into the surface's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub colours: Option<[[f32; 3]; 3]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transform: Option<TransformDescription>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transform_end: Option<TransformDescription>,
+}
+
+// a parallelogram with corners at a, b, c and b + c - a
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct QuadDescription {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub a: [f32; 3],
+    pub b: [f32; 3],
+    pub c: [f32; 3],
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub surface: Option<SurfaceRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transform: Option<TransformDescription>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transform_end: Option<TransformDescription>,
+}
+
+// goes on forever through a, b and c
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct PlaneDescription {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub a: [f32; 3],
+    pub b: [f32; 3],
+    pub c: [f32; 3],
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub surface: Option<SurfaceRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transform: Option<TransformDescription>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transform_end: Option<TransformDescription>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct PointLightDescription {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub origin: [f32; 3],
+    // for now this is how close a ray has to pass to see the light
+    pub intensity: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transform: Option<TransformDescription>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transform_end: Option<TransformDescription>,
+}
+
+// the sky, fading from bottom to top. a dim grey from above if left out
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct WorldLightDescription {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top: Option<[f32; 3]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bottom: Option<[f32; 3]>,
+}
+
+// what an item is made of, either the name of one of the materials or a surface written out in
+// full. a specular surface with the default colour if an item doesnt say
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum SurfaceRef {
+    Material(String),
+    Surface(SurfaceDescription),
+}
+
+// a file of materials for scenes to import. scene files work as libraries too
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct LibraryDescription {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub import_materials: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub materials: BTreeMap<String, SurfaceDescription>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SurfaceDescription {
+    Specular(SpecularDescription),
+    Diffuse(DiffuseDescription),
+    MetallicRoughness(MetallicRoughnessDescription),
+    Emissive(EmissiveDescription),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SpecularDescription {
+    pub colour: [f32; 3],
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct DiffuseDescription {
+    pub colour: [f32; 3],
+    // how many rays get sent off each time something hits it
+    pub samples: usize,
+}
+
+// what gltf files use, metallic and roughness both go from 0 to 1
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct MetallicRoughnessDescription {
+    pub colour: [f32; 3],
+    pub metallic: f32,
+    pub roughness: f32,
+    // how many rays get sent off each time something hits it, when it isnt a perfect mirror
+    pub samples: usize,
+}
+
+// glows with its own light, colours can go over 1 for brighter lamps
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct EmissiveDescription {
+    pub colour: [f32; 3],
+}
+
+// applied in the order scale, rotate, translate
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct TransformDescription {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub translate: Option<[f32; 3]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotate: Option<RotateDescription>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<f32>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct RotateDescription {
+    pub axis: [f32; 3],
+    // degrees
+    pub angle: f32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AnimationDescription {
+    // first and last frame, both included. up to the last key if left out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frames: Option<[u32; 2]>,
+    pub tracks: Vec<TrackDescription>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct TrackDescription {
+    // where in the scene file the value goes, like cameras/front/position
+    pub target: String,
+    // for any keys that dont say, linear if left out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interpolation: Option<Interpolation>,
+    pub keys: Vec<KeyDescription>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct KeyDescription {
+    pub frame: f32,
+    pub value: Numbers,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interpolation: Option<Interpolation>,
+    // bezier control points either side of the key
+    #[serde(rename = "in", default, skip_serializing_if = "Option::is_none")]
+    pub in_handle: Option<Numbers>,
+    #[serde(rename = "out", default, skip_serializing_if = "Option::is_none")]
+    pub out_handle: Option<Numbers>,
+}
+
+// a single number or a list of them
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum Numbers {
+    One(f32),
+    Many(Vec<f32>),
+}
+
+pub const SURFACE_TYPES: [&str; 4] = ["specular", "diffuse", "metallic_roughness", "emissive"];
+
+const ITEM_KINDS: [&str; 6] = [
+    "sphere",
+    "triangle",
+    "quad",
+    "plane",
+    "point_light",
+    "world_light",
+];
+
+impl SceneDescription {
+    // reads each part of the file on its own, so that everything wrong with it gets found rather
+    // than just the first thing
+    pub fn from_value(contents: &Value) -> Result<SceneDescription, Vec<Problem>> {
+        let mut problems = vec![];
+        if !contents.is_object() {
+            return Err(vec![Problem {
+                location: Location::Path(String::new()),
+                reason: Reason::WrongType {
+                    expected: String::from("an object with the scene in it"),
+                },
+            }]);
+        }
+        let includes = read_list(contents, "includes", false, &mut problems, read);
+        let mut description = SceneDescription {
+            items: read_list(
+                contents,
+                "items",
+                includes.is_empty(),
+                &mut problems,
+                read_item,
+            ),
+            includes,
+            settings: read_field(contents, "settings", &mut problems),
+            cameras: read_list(contents, "cameras", false, &mut problems, read),
+            camera: read_field(contents, "camera", &mut problems),
+            default_camera: read_field(contents, "default_camera", &mut problems),
+            world: read_field(contents, "world", &mut problems),
+            import_materials: read_field(contents, "import_materials", &mut problems)
+                .unwrap_or_default(),
+            materials: read_materials(contents, &mut problems),
+            animation: read_field(contents, "animation", &mut problems),
+            schema: None,
+        };
+        description.schema = read_field(contents, "$schema", &mut problems);
+        if !problems.is_empty() {
+            return Err(problems);
+        }
+        Ok(description)
+    }
+
+    // the same description as the file would be written at a frame of its animation, which
+    // does without the animation itself
+    pub fn without_animation(&self) -> SceneDescription {
+        SceneDescription {
+            animation: None,
+            ..self.clone()
+        }
+    }
+}
+
+impl LibraryDescription {
+    pub fn from_value(contents: &Value) -> Result<LibraryDescription, Vec<Problem>> {
+        let mut problems = vec![];
+        if !contents.is_object() {
+            return Err(vec![Problem {
+                location: Location::Path(String::new()),
+                reason: Reason::WrongType {
+                    expected: String::from("an object with the materials in it"),
+                },
+            }]);
+        }
+        let library = LibraryDescription {
+            import_materials: read_field(contents, "import_materials", &mut problems)
+                .unwrap_or_default(),
+            materials: read_materials(contents, &mut problems),
+        };
+        if !problems.is_empty() {
+            return Err(problems);
+        }
+        Ok(library)
+    }
+}
+
+// the json schema for scene files, for editors to check them with
+pub fn json_schema() -> String {
+    serde_json::to_string_pretty(&schemars::schema_for!(SceneDescription)).unwrap() + "\n"
+}
+
+fn read<T: DeserializeOwned>(value: &Value, path: &str, problems: &mut Vec<Problem>) -> Option<T> {
+    match serde_path_to_error::deserialize(value) {
+        Ok(read) => Some(read),
+        Err(error) => {
+            problems.push(serde_problem(value, path, error));
+            None
+        }
+    }
+}
+
+// left out and null are both the same as not being there
+fn read_field<T: DeserializeOwned>(
+    data: &Value,
+    key: &str,
+    problems: &mut Vec<Problem>,
+) -> Option<T> {
+    match data.get(key) {
+        None | Some(Value::Null) => None,
+        Some(value) => read(value, key, problems),
+    }
+}
+
+// every item in the list that could be read
+fn read_list<T>(
+    data: &Value,
+    key: &str,
+    required: bool,
+    problems: &mut Vec<Problem>,
+    read_one: impl Fn(&Value, &str, &mut Vec<Problem>) -> Option<T>,
+) -> Vec<T> {
+    let problem = |reason| Problem {
+        location: Location::Path(key.to_string()),
+        reason,
+    };
+    match data.get(key) {
+        None | Some(Value::Null) => {
+            if required {
+                problems.push(problem(Reason::Missing));
+            }
+            vec![]
+        }
+        Some(Value::Array(values)) => values
+            .iter()
+            .enumerate()
+            .filter_map(|(i, value)| read_one(value, &index(key, i), problems))
+            .collect(),
+        Some(_) => {
+            problems.push(problem(Reason::WrongType {
+                expected: String::from("a list"),
+            }));
+            vec![]
+        }
+    }
+}
+
+// the field saying what kind of thing this is, which has to be there and be a string
+fn read_tag<'a>(
+    data: &'a Value,
+    path: &str,
+    tag: &str,
+    problems: &mut Vec<Problem>,
+) -> Option<&'a str> {
+    let problem = |reason| Problem {
+        location: Location::Path(join(path, tag)),
+        reason,
+    };
+    match data.get(tag) {
+        Some(Value::String(kind)) => Some(kind.as_str()),
+        None | Some(Value::Null) => {
+            problems.push(problem(Reason::Missing));
+            None
+        }
+        Some(_) => {
+            problems.push(problem(Reason::WrongType {
+                expected: String::from("a string"),
+            }));
+            None
+        }
+    }
+}
+
+fn unknown_kind(path: &str, tag: &str, found: &str, expected: &[&str]) -> Problem {
+    Problem {
+        location: Location::Path(join(path, tag)),
+        reason: Reason::UnknownKind {
+            found: found.to_string(),
+            expected: expected.iter().map(|kind| kind.to_string()).collect(),
+        },
+    }
+}
+
+// serde would read an item fine by itself, but it loses track of where in the item a problem was
+// when it has to look at the kind first. so the kind gets picked out here instead
+fn read_item(data: &Value, path: &str, problems: &mut Vec<Problem>) -> Option<ItemDescription> {
+    // a surface without a type was always the default specular one, the same as leaving it out
+    let untyped = match data.get("surface") {
+        Some(Value::Object(surface)) => surface.get("type").is_none_or(Value::is_null),
+        _ => false,
+    };
+    let without_surface;
+    let data = if untyped {
+        let mut copy = data.clone();
+        copy.as_object_mut()?.remove("surface");
+        without_surface = copy;
+        &without_surface
+    } else {
+        data
+    };
+    match read_tag(data, path, "kind", problems)? {
+        "sphere" => read(data, path, problems).map(ItemDescription::Sphere),
+        "triangle" => read_triangle(data, path, problems).map(ItemDescription::Triangle),
+        "quad" => read(data, path, problems).map(ItemDescription::Quad),
+        "plane" => read(data, path, problems).map(ItemDescription::Plane),
+        "point_light" => read(data, path, problems).map(ItemDescription::PointLight),
+        "world_light" => read(data, path, problems).map(ItemDescription::WorldLight),
+        kind => {
+            problems.push(unknown_kind(path, "kind", kind, &ITEM_KINDS));
+            None
+        }
+    }
+}
+
+// triangles used to have a flat colour instead of a surface, which is the same as a diffuse one
+fn read_triangle(
+    data: &Value,
+    path: &str,
+    problems: &mut Vec<Problem>,
+) -> Option<TriangleDescription> {
+    let Some(colour) = data.get("colour") else {
+        return read(data, path, problems);
+    };
+    let mut data = data.clone();
+    let object = data.as_object_mut()?;
+    object.remove("colour");
+    if object.contains_key("surface") {
+        problems.push(Problem {
+            location: Location::Path(join(path, "colour")),
+            reason: Reason::Invalid(String::from(
+                "colour is the old way of giving a diffuse surface, it cant go with a surface",
+            )),
+        });
+        return None;
+    }
+    let colour = read::<[f32; 3]>(colour, &join(path, "colour"), problems)?;
+    let mut triangle: TriangleDescription = read(&data, path, problems)?;
+    triangle.surface = Some(SurfaceRef::Surface(SurfaceDescription::Diffuse(
+        DiffuseDescription { colour, samples: 3 },
+    )));
+    Some(triangle)
+}
+
+// the same as read_item, for the type of a surface
+fn read_surface(
+    data: &Value,
+    path: &str,
+    problems: &mut Vec<Problem>,
+) -> Option<SurfaceDescription> {
+    if !data.is_object() {
+        problems.push(Problem {
+            location: Location::Path(path.to_string()),
+            reason: Reason::WrongType {
+                expected: String::from("a surface"),
+            },
+        });
+        return None;
+    }
+    match read_tag(data, path, "type", problems)? {
+        "specular" => read(data, path, problems).map(SurfaceDescription::Specular),
+        "diffuse" => read(data, path, problems).map(SurfaceDescription::Diffuse),
+        "metallic_roughness" => {
+            read(data, path, problems).map(SurfaceDescription::MetallicRoughness)
+        }
+        "emissive" => read(data, path, problems).map(SurfaceDescription::Emissive),
+        kind => {
+            problems.push(unknown_kind(path, "type", kind, &SURFACE_TYPES));
+            None
+        }
+    }
+}
+
+fn read_materials(
+    data: &Value,
+    problems: &mut Vec<Problem>,
+) -> BTreeMap<String, SurfaceDescription> {
+    match data.get("materials") {
+        None | Some(Value::Null) => BTreeMap::new(),
+        Some(Value::Object(materials)) => materials
+            .iter()
+            .filter_map(|(name, surface)| {
+                let surface = read_surface(surface, &join("materials", name), problems)?;
+                Some((name.clone(), surface))
+            })
+            .collect(),
+        Some(_) => {
+            problems.push(Problem {
+                location: Location::Path(String::from("materials")),
+                reason: Reason::WrongType {
+                    expected: String::from("an object of named surfaces"),
+                },
+            });
+            BTreeMap::new()
+        }
+    }
+}
+
+// turns what serde says went wrong into one of our problems. value is the part of the file that was
+// being read, and path is where it is in the file
+fn serde_problem(
+    value: &Value,
+    path: &str,
+    error: serde_path_to_error::Error<serde_json::Error>,
+) -> Problem {
+    let mut path = path.to_string();
+    let mut pointer = String::new();
+    for segment in error.path().iter() {
+        match segment {
+            serde_path_to_error::Segment::Seq { index: i } => {
+                path = index(&path, *i);
+                pointer = format!("{}/{}", pointer, i);
+            }
+            serde_path_to_error::Segment::Map { key } => {
+                path = join(&path, key);
+                pointer = format!("{}/{}", pointer, key);
+            }
+            _ => {}
+        }
+    }
+    let message = error.into_inner().to_string();
+    let backticked = |text: &str| {
+        text.split('`')
+            .skip(1)
+            .step_by(2)
+            .map(String::from)
+            .collect::<Vec<String>>()
+    };
+
+    let reason = if let Some(field) = message.strip_prefix("missing field `") {
+        path = join(&path, field.trim_end_matches('`'));
+        Reason::Missing
+    } else if let Some(rest) = message.strip_prefix("unknown variant ") {
+        let (found, expected) = rest.split_once(", expected").unwrap_or((rest, ""));
+        let found = backticked(found).pop().unwrap_or_default();
+        // a kind or type field that was picked out by serde before it got to the rest
+        if let Some(fields) = value.pointer(&pointer).and_then(Value::as_object) {
+            if let Some((tag, _)) = fields
+                .iter()
+                .find(|(_, tag)| tag.as_str() == Some(found.as_str()))
+            {
+                path = join(&path, tag);
+            }
+        }
+        Reason::UnknownKind {
+            found,
+            expected: backticked(expected),
+        }
+    } else if let Some(rest) = message.strip_prefix("invalid type: ") {
+        let expected = rest.split_once(", expected ").map_or("", |(_, e)| e);
+        Reason::WrongType {
+            expected: in_words(expected),
+        }
+    } else if let Some(rest) = message.strip_prefix("invalid length ") {
+        let (found, expected) = rest.split_once(", expected ").unwrap_or((rest, ""));
+        match (
+            found.parse(),
+            expected.rsplit(' ').next().unwrap_or("").parse(),
+        ) {
+            (Ok(found), Ok(expected)) => Reason::BadVectorLength { expected, found },
+            _ => Reason::Invalid(message.clone()),
+        }
+    } else if message.starts_with("data did not match any variant of untagged enum Numbers") {
+        Reason::WrongType {
+            expected: String::from("a number or a list of numbers"),
+        }
+    } else {
+        Reason::Invalid(message.clone())
+    };
+    // serde only knows a problem is somewhere in a surface or projection, because it has to find
+    // the type before it can read the rest. reading it again as that type finds exactly where
+    if !matches!(reason, Reason::Missing | Reason::UnknownKind { .. }) {
+        if let Some(problem) = value
+            .pointer(&pointer)
+            .and_then(|tagged| read_tagged(tagged, &path))
+        {
+            return problem;
+        }
+    }
+    Problem {
+        location: Location::Path(path),
+        reason,
+    }
+}
+
+fn read_tagged(data: &Value, path: &str) -> Option<Problem> {
+    let mut problems = vec![];
+    match path.rsplit('.').next()? {
+        "surface" if !data.is_object() => {
+            return Some(Problem {
+                location: Location::Path(path.to_string()),
+                reason: Reason::WrongType {
+                    expected: String::from("the name of a material or a surface"),
+                },
+            })
+        }
+        "surface" => read_surface(data, path, &mut problems).map(drop),
+        "projection" => match data.get("type")?.as_str()? {
+            "orthographic" => read::<OrthographicDescription>(data, path, &mut problems).map(drop),
+            "fisheye" => read::<FisheyeDescription>(data, path, &mut problems).map(drop),
+            _ => None,
+        },
+        _ => None,
+    };
+    problems.pop()
+}
+
+// what serde expected, the way the rest of the problems say it
+fn in_words(expected: &str) -> String {
+    let words = match expected {
+        "f32" | "f64" => "a number",
+        "u8" | "u32" | "u64" | "usize" => "a whole number, 0 or more",
+        "a string" => "a string",
+        "a sequence" => "a list",
+        _ if expected.starts_with("an array") => "a list of numbers",
+        _ if expected.starts_with("struct") || expected.starts_with("internally tagged") => {
+            "an object"
+        }
+        _ => expected,
+    };
+    words.to_string()
+}
+
+fn vector(v: [f32; 3]) -> Vector3<f32> {
+    Vector3::new(v[0], v[1], v[2])
+}
+
+fn array(v: &Vector3<f32>) -> [f32; 3] {
+    [v.x, v.y, v.z]
+}
+
+fn colour(c: [f32; 3]) -> Rgba {
+    Rgba::from_rgb(c[0], c[1], c[2])
+}
+
+pub fn colour_array(c: Rgba) -> [f32; 3] {
+    [c.r(), c.g(), c.b()]
+}
+
+impl ItemDescription {
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            ItemDescription::Sphere(sphere) => sphere.name.as_deref(),
+            ItemDescription::Triangle(triangle) => triangle.name.as_deref(),
+            ItemDescription::Quad(quad) => quad.name.as_deref(),
+            ItemDescription::Plane(plane) => plane.name.as_deref(),
+            ItemDescription::PointLight(light) => light.name.as_deref(),
+            ItemDescription::WorldLight(light) => light.name.as_deref(),
+        }
+    }
+
+    pub fn set_name(&mut self, name: Option<String>) {
+        match self {
+            ItemDescription::Sphere(sphere) => sphere.name = name,
+            ItemDescription::Triangle(triangle) => triangle.name = name,
+            ItemDescription::Quad(quad) => quad.name = name,
+            ItemDescription::Plane(plane) => plane.name = name,
+            ItemDescription::PointLight(light) => light.name = name,
+            ItemDescription::WorldLight(light) => light.name = name,
+        }
+    }
+
+    // for the kinds of item that have a surface
+    pub fn surface_mut(&mut self) -> Option<&mut Option<SurfaceRef>> {
+        match self {
+            ItemDescription::Sphere(sphere) => Some(&mut sphere.surface),
+            ItemDescription::Triangle(triangle) => Some(&mut triangle.surface),
+            ItemDescription::Quad(quad) => Some(&mut quad.surface),
+            ItemDescription::Plane(plane) => Some(&mut plane.surface),
+            ItemDescription::PointLight(_) | ItemDescription::WorldLight(_) => None,
+        }
+    }
+
+    // the transform and transform_end, for the kinds of item that can be moved
+    pub fn transforms(&self) -> (Option<&TransformDescription>, Option<&TransformDescription>) {
+        match self {
+            ItemDescription::Sphere(sphere) => {
+                (sphere.transform.as_ref(), sphere.transform_end.as_ref())
+            }
+            ItemDescription::Triangle(triangle) => {
+                (triangle.transform.as_ref(), triangle.transform_end.as_ref())
+            }
+            ItemDescription::Quad(quad) => (quad.transform.as_ref(), quad.transform_end.as_ref()),
+            ItemDescription::Plane(plane) => {
+                (plane.transform.as_ref(), plane.transform_end.as_ref())
+            }
+            ItemDescription::PointLight(light) => {
+                (light.transform.as_ref(), light.transform_end.as_ref())
+            }
+            ItemDescription::WorldLight(_) => (None, None),
+        }
+    }
+
+    pub fn transforms_mut(
+        &mut self,
+    ) -> Option<(
+        &mut Option<TransformDescription>,
+        &mut Option<TransformDescription>,
+    )> {
+        match self {
+            ItemDescription::Sphere(sphere) => {
+                Some((&mut sphere.transform, &mut sphere.transform_end))
+            }
+            ItemDescription::Triangle(triangle) => {
+                Some((&mut triangle.transform, &mut triangle.transform_end))
+            }
+            ItemDescription::Quad(quad) => Some((&mut quad.transform, &mut quad.transform_end)),
+            ItemDescription::Plane(plane) => Some((&mut plane.transform, &mut plane.transform_end)),
+            ItemDescription::PointLight(light) => {
+                Some((&mut light.transform, &mut light.transform_end))
+            }
+            // it is infinitely far away, so moving it does nothing
+            ItemDescription::WorldLight(_) => None,
+        }
+    }
+
+    // the only thing that can be wrong by now is the surface being a material that isnt there
+    pub fn build(
+        &self,
+        materials: &BTreeMap<String, Material>,
+    ) -> Result<Arc<dyn Intersect>, Reason> {
+        let object: Arc<dyn Intersect> = match self {
+            ItemDescription::Sphere(sphere) => {
+                let mut built = Sphere::with_shader(
+                    vector(sphere.origin),
+                    sphere.radius,
+                    build_surface(&sphere.surface, materials)?,
+                );
+                built.velocity = sphere.velocity.map(vector).unwrap_or(built.velocity);
+                Arc::new(built)
+            }
+            ItemDescription::Triangle(triangle) => {
+                let [a, b, c] = [triangle.a, triangle.b, triangle.c].map(vector);
+                let built = Triangle::from_3_points_and_surface(
+                    &a,
+                    &b,
+                    &c,
+                    build_surface(&triangle.surface, materials)?,
+                );
+                Arc::new(built.with_shading(Shading {
+                    normals: triangle.normals.map(|normals| normals.map(vector)),
+                    uvs: triangle.uvs.map(|uvs| uvs.map(Vector2::from)),
+                    colours: triangle.colours.map(|colours| colours.map(colour)),
+                }))
+            }
+            ItemDescription::Quad(quad) => {
+                let [a, b, c] = [quad.a, quad.b, quad.c].map(vector);
+                Arc::new(Quad::from_3_points_and_surface(
+                    &a,
+                    &b,
+                    &c,
+                    build_surface(&quad.surface, materials)?,
+                ))
+            }
+            ItemDescription::Plane(plane) => {
+                let [a, b, c] = [plane.a, plane.b, plane.c].map(vector);
+                Arc::new(Plane::from_3_points_and_surface(
+                    &a,
+                    &b,
+                    &c,
+                    build_surface(&plane.surface, materials)?,
+                ))
+            }
+            ItemDescription::PointLight(light) => {
+                Arc::new(PointLight::new(vector(light.origin), light.intensity))
+            }
+            ItemDescription::WorldLight(light) => {
+                let mut built = WorldLight::default();
+                built.top = light.top.map(colour).unwrap_or(built.top);
+                built.bottom = light.bottom.map(colour).unwrap_or(built.bottom);
+                Arc::new(built)
+            }
+        };
+
+        // objects with a transform get wrapped up in one, and if there is a transform_end as well
+        // they move from one to the other while the shutter is open
+        let (start, end) = self.transforms();
+        Ok(
+            match (
+                start.map(TransformDescription::build),
+                end.map(TransformDescription::build),
+            ) {
+                (None, None) => object,
+                (Some(start), None) => Arc::new(Transformed::new(object, start)),
+                (start, Some(end)) => {
+                    Arc::new(Transformed::moving(object, start.unwrap_or_default(), end))
+                }
+            },
+        )
+    }
+}
+
+// materials are built once up front and shared by everything that uses them
+fn build_surface(
+    surface: &Option<SurfaceRef>,
+    materials: &BTreeMap<String, Material>,
+) -> Result<Arc<dyn Surface>, Reason> {
+    match surface {
+        None => Ok(Arc::new(Specular::new())),
+        Some(SurfaceRef::Surface(surface)) => Ok(surface.build()),
+        Some(SurfaceRef::Material(name)) => match materials.get(name) {
+            Some(material) => Ok(material.surface.clone()),
+            None => Err(Reason::UnknownKind {
+                found: name.clone(),
+                expected: materials.keys().cloned().collect(),
+            }),
+        },
+    }
+}
+
+impl SurfaceDescription {
+    pub fn build(&self) -> Arc<dyn Surface> {
+        match self {
+            SurfaceDescription::Specular(specular) => {
+                Arc::new(Specular::with_colour(colour(specular.colour)))
+            }
+            SurfaceDescription::Diffuse(diffuse) => {
+                Arc::new(Diffuse::new(colour(diffuse.colour), diffuse.samples))
+            }
+            SurfaceDescription::MetallicRoughness(surface) => Arc::new(MetallicRoughness::new(
+                colour(surface.colour),
+                surface.metallic,
+                surface.roughness,
+                surface.samples,
+            )),
+            SurfaceDescription::Emissive(emissive) => {
+                Arc::new(Emissive::new(colour(emissive.colour)))
+            }
+        }
+    }
+}
+
+impl TransformDescription {
+    pub fn build(&self) -> Transform {
+        let mut transform = Transform::default();
+        if let Some(translation) = self.translate {
+            transform.translation = vector(translation);
+        }
+        if let Some(rotate) = &self.rotate {
+            transform.rotation = UnitQuaternion::from_axis_angle(
+                &Unit::new_normalize(vector(rotate.axis)),
+                rotate.angle.to_radians(),
+            );
+        }
+        if let Some(scale) = self.scale {
+            transform.scale = scale;
+        }
+        transform
+    }
+
+    // anything still at its default gets left out
+    pub fn describe(transform: &Transform) -> TransformDescription {
+        let default = Transform::default();
+        TransformDescription {
+            translate: (transform.translation != default.translation)
+                .then(|| array(&transform.translation)),
+            rotate: transform
+                .rotation
+                .axis_angle()
+                .map(|(axis, angle)| RotateDescription {
+                    axis: array(&axis),
+                    angle: angle.to_degrees(),
+                }),
+            scale: (transform.scale != default.scale).then_some(transform.scale),
+        }
+    }
+}
+
+impl SettingsDescription {
+    pub fn build(&self) -> RenderSettings {
+        let mut settings = RenderSettings::default();
+        settings.sampler = self.sampler.unwrap_or(settings.sampler);
+        settings.samples_per_pixel = self.samples_per_pixel.unwrap_or(settings.samples_per_pixel);
+        settings.seed = self.seed.unwrap_or(settings.seed);
+        settings.filter = self.filter.unwrap_or(settings.filter);
+        settings.max_depth = self.max_depth.or(settings.max_depth);
+        settings.integrator = self.integrator.unwrap_or(settings.integrator);
+        settings.adaptive = self.adaptive.as_ref().map(|adaptive| {
+            let mut built = AdaptiveSettings::default();
+            built.noise_threshold = adaptive.noise_threshold.unwrap_or(built.noise_threshold);
+            built.max_samples_per_pixel = adaptive
+                .max_samples_per_pixel
+                .unwrap_or(built.max_samples_per_pixel);
+            built
+        });
+        settings
+    }
+
+    // the width and height go in here too, since RenderSettings doesnt have them
+    pub fn describe(settings: &RenderSettings, (width, height): (u32, u32)) -> SettingsDescription {
+        SettingsDescription {
+            width: Some(width),
+            height: Some(height),
+            sampler: Some(settings.sampler),
+            samples_per_pixel: Some(settings.samples_per_pixel),
+            seed: Some(settings.seed),
+            filter: Some(settings.filter),
+            max_depth: settings.max_depth,
+            integrator: Some(settings.integrator),
+            adaptive: settings.adaptive.map(|adaptive| AdaptiveDescription {
+                noise_threshold: Some(adaptive.noise_threshold),
+                max_samples_per_pixel: Some(adaptive.max_samples_per_pixel),
+            }),
+        }
+    }
+
+    // how big pictures are when the camera doesnt say
+    pub fn resolution(&self) -> (u32, u32) {
+        (self.width.unwrap_or(600), self.height.unwrap_or(600))
+    }
+}
+
+impl CameraDescription {
+    pub fn build(&self, resolution: (u32, u32)) -> Camera {
+        let mut camera = Camera::look_at(
+            vector(self.position),
+            vector(self.target),
+            self.width.unwrap_or(resolution.0),
+            self.height.unwrap_or(resolution.1),
+        );
+        camera.up = self.up.map(vector).unwrap_or(camera.up);
+        camera.vertical_fov = self.fov.unwrap_or(camera.vertical_fov);
+        camera.aperture_radius = self.aperture.unwrap_or(camera.aperture_radius);
+        camera.focus_distance = self.focus_distance.unwrap_or(camera.focus_distance);
+        camera.aperture_blades = self.blades.unwrap_or(0);
+        if let Some(projection) = &self.projection {
+            camera.projection = projection.build();
+        }
+        camera.stereo = self.stereo.as_ref().map(StereoDescription::build);
+        camera.shutter = self
+            .shutter
+            .map(|[open, close]| (open, close))
+            .unwrap_or(camera.shutter);
+        camera
+    }
+
+    // the width and height only get written if they are different to the resolution in the
+    // settings
+    pub fn describe(name: &str, camera: &Camera, resolution: (u32, u32)) -> CameraDescription {
+        CameraDescription {
+            name: Some(name.to_string()),
+            position: array(&camera.position),
+            target: array(&camera.target),
+            up: Some(array(&camera.up)),
+            fov: Some(camera.vertical_fov),
+            aperture: Some(camera.aperture_radius),
+            focus_distance: Some(camera.focus_distance),
+            blades: Some(camera.aperture_blades),
+            projection: Some(camera.projection.describe()),
+            stereo: camera.stereo.as_ref().map(StereoDescription::describe),
+            shutter: Some([camera.shutter.0, camera.shutter.1]),
+            width: (camera.width != resolution.0).then_some(camera.width),
+            height: (camera.height != resolution.1).then_some(camera.height),
+        }
+    }
+}
+
+impl ProjectionDescription {
+    pub fn build(&self) -> Arc<dyn Projection> {
+        match self {
+            ProjectionDescription::Perspective => Arc::new(Perspective {}),
+            ProjectionDescription::Orthographic(orthographic) => {
+                Arc::new(Orthographic::new(orthographic.height.unwrap_or(4.0)))
+            }
+            ProjectionDescription::Fisheye(fisheye) => {
+                Arc::new(Fisheye::new(fisheye.fov.unwrap_or(180.0)))
+            }
+            ProjectionDescription::Equirectangular => Arc::new(Equirectangular::new()),
+        }
+    }
+}
+
+impl StereoDescription {
+    pub fn build(&self) -> Stereo {
+        let mut stereo = Stereo::default();
+        stereo.interpupillary_distance = self.ipd.unwrap_or(stereo.interpupillary_distance);
+        stereo.convergence_distance = self.convergence.unwrap_or(stereo.convergence_distance);
+        stereo.layout = self.layout.unwrap_or(stereo.layout);
+        stereo
+    }
+
+    pub fn describe(stereo: &Stereo) -> StereoDescription {
+        StereoDescription {
+            ipd: Some(stereo.interpupillary_distance),
+            convergence: Some(stereo.convergence_distance),
+            layout: Some(stereo.layout),
+        }
+    }
+}
+
+impl Numbers {
+    pub fn to_vec(&self) -> Vec<f32> {
+        match self {
+            Numbers::One(number) => vec![*number],
+            Numbers::Many(numbers) => numbers.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::json_schema;
+
+    // editors read the copy in jsons/, so it has to keep up with the types
+    #[test]
+    fn published_schema_is_up_to_date() {
+        let published = std::fs::read_to_string("jsons/scene.schema.json").unwrap();
+        assert!(
+            published == json_schema(),
+            "jsons/scene.schema.json is out of date, regenerate it with `render --schema`"
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use std::fmt::{self, Display};
+
+// everything that was wrong with a scene file, so it can all be fixed in one go
+#[derive(Clone, Debug, PartialEq)]
+pub struct SceneError {
+    pub file: String,
+    pub problems: Vec<Problem>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Problem {
+    pub location: Location,
+    pub reason: Reason,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Location {
+    // the file as a whole, like when it cant be opened
+    File,
+    // counting from 1, with the column if we know it
+    Line(usize, Option<usize>),
+    // where in a json file, like items[2].surface.colour
+    Path(String),
+    // somewhere in another file this one pulled in, like a material library
+    InFile(String, Box<Location>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Reason {
+    Unreadable(String),
+    NotUtf8,
+    // the file isnt valid json (or csv) at all
+    Syntax(String),
+    Missing,
+    WrongType {
+        expected: String,
+    },
+    UnknownKind {
+        found: String,
+        expected: Vec<String>,
+    },
+    BadVectorLength {
+        expected: usize,
+        found: usize,
+    },
+    // for lists where some of the numbers can be left off the end
+    BadNumberCount {
+        fewest: usize,
+        most: usize,
+        found: usize,
+    },
+    BadNumber(String),
+    // anything else, in words
+    Invalid(String),
+}
+
+impl SceneError {
+    pub fn new(file: &str, problems: Vec<Problem>) -> SceneError {
+        SceneError {
+            file: file.to_string(),
+            problems,
+        }
+    }
+
+    // for when there is only one thing wrong, like the file not being there
+    pub fn single(file: &str, location: Location, reason: Reason) -> SceneError {
+        SceneError::new(file, vec![Problem { location, reason }])
+    }
+
+    // for when this file was loaded by another one, so each problem still says which file its in
+    pub fn into_problems(self) -> Vec<Problem> {
+        let file = self.file;
+        self.problems
+            .into_iter()
+            .map(|problem| Problem {
+                location: Location::InFile(file.clone(), Box::new(problem.location)),
+                reason: problem.reason,
+            })
+            .collect()
+    }
+}
+
+impl Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = self.problems.len();
+        write!(
+            f,
+            "{} problem{} loading {}",
+            count,
+            if count == 1 { "" } else { "s" },
+            self.file
+        )?;
+        for problem in &self.problems {
+            write!(f, "\n  {}", problem.describe(&self.file))?;
+        }
+        Ok(())
+    }
+}
+
+impl Problem {
+    // where and what, like a line of a SceneError. for things that didnt stop a file loading
+    pub fn describe(&self, file: &str) -> String {
+        let mut text = String::new();
+        write_location(&mut text, file, &self.location).unwrap();
+        text + ": " + &self.reason.to_string()
+    }
+}
+
+fn write_location(f: &mut impl fmt::Write, file: &str, location: &Location) -> fmt::Result {
+    match location {
+        Location::File => write!(f, "{}", file),
+        Location::Line(line, None) => write!(f, "{}:{}", file, line),
+        Location::Line(line, Some(column)) => write!(f, "{}:{}:{}", file, line, column),
+        Location::Path(path) => write!(f, "{} at {}", file, path),
+        Location::InFile(inner, location) => write_location(f, inner, location),
+    }
+}
+
+impl Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reason::Unreadable(why) => write!(f, "couldnt read it ({})", why),
+            Reason::NotUtf8 => write!(f, "isnt utf-8 text"),
+            Reason::Syntax(why) => write!(f, "{}", why),
+            Reason::Missing => write!(f, "missing"),
+            Reason::WrongType { expected } => write!(f, "should be {}", expected),
+            Reason::UnknownKind { found, expected } => {
+                write!(
+                    f,
+                    "unknown {}, expected one of {}",
+                    found,
+                    expected.join(", ")
+                )
+            }
+            Reason::BadVectorLength { expected, found } => {
+                write!(f, "should have {} numbers but has {}", expected, found)
+            }
+            Reason::BadNumberCount {
+                fewest,
+                most,
+                found,
+            } => write!(
+                f,
+                "should have {} to {} numbers but has {}",
+                fewest, most, found
+            ),
+            Reason::BadNumber(text) => write!(f, "{:?} isnt a number", text),
+            Reason::Invalid(why) => write!(f, "{}", why),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
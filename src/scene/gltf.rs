@@ -0,0 +1,578 @@
+use std::{collections::HashMap, sync::Arc};
+
+use ::gltf::{
+    buffer, camera::Projection as GltfProjection, image, khr_lights_punctual::Kind, mesh::Mode,
+    texture, Document, Node,
+};
+use epaint::Rgba;
+use nalgebra::{Matrix3, Matrix4, Point3, Vector2, Vector3, Vector4};
+
+use crate::{
+    camera::Camera,
+    objects::{
+        light::PointLight,
+        triangle::{Shading, Triangle},
+        world_light::WorldLight,
+    },
+    projection::orthographic::Orthographic,
+    scene::{
+        error::{Location, Problem, Reason, SceneError},
+        json::relative_to,
+        materials::Material,
+        NamedCamera, Scene, DEPTH,
+    },
+    surfaces::{metallic_roughness::MetallicRoughness, texture::Texture, Surface},
+};
+
+// how many rays rough surfaces send off when something hits them
+const SAMPLES: usize = 3;
+
+// gltf lights are just points, but ours need a size to be seen
+const LIGHT_RADIUS: f32 = 0.1;
+
+// gltf cameras only give an aspect ratio, so they are this many pixels tall
+const CAMERA_HEIGHT: u32 = 600;
+
+impl Scene {
+    // a .gltf file (with whatever .bin and pictures it points at) or a .glb. the default scene's
+    // nodes are walked with their transforms, meshes become triangles (with any vertex colours),
+    // materials become metallic roughness surfaces, and cameras and punctual lights come along
+    // too. gltf has y pointing up, so everything gets turned so that z is up like everywhere
+    // else here
+    pub fn from_gltf(file_name: &str) -> Result<Scene, SceneError> {
+        let (document, buffers, images) = ::gltf::import(file_name).map_err(|error| {
+            let reason = match error {
+                ::gltf::Error::Io(error) => Reason::Unreadable(error.to_string()),
+                error => Reason::Syntax(error.to_string()),
+            };
+            SceneError::single(file_name, Location::File, reason)
+        })?;
+
+        let mut importer = Importer {
+            buffers: &buffers,
+            images: &images,
+            textures: HashMap::new(),
+            surfaces: vec![],
+            scene: Scene::new(DEPTH),
+        };
+        importer.scene.sources = sources(&document, file_name);
+        importer.warn_about_losses(&document);
+        importer.add_materials(&document);
+        let y_up_to_z_up = Matrix4::new(
+            1.0, 0.0, 0.0, 0.0, //
+            0.0, 0.0, -1.0, 0.0, //
+            0.0, 1.0, 0.0, 0.0, //
+            0.0, 0.0, 0.0, 1.0,
+        );
+        if let Some(scene) = document.default_scene().or(document.scenes().next()) {
+            for node in scene.nodes() {
+                importer.add_node(&node, &y_up_to_z_up);
+            }
+        }
+        Ok(importer.scene)
+    }
+}
+
+// the gltf file and any buffers and pictures it keeps in files next to it
+fn sources(document: &Document, file_name: &str) -> Vec<String> {
+    let buffers = document
+        .buffers()
+        .filter_map(|buffer| match buffer.source() {
+            buffer::Source::Uri(uri) => Some(uri),
+            buffer::Source::Bin => None,
+        });
+    let images = document.images().filter_map(|image| match image.source() {
+        image::Source::Uri { uri, .. } => Some(uri),
+        image::Source::View { .. } => None,
+    });
+    let mut sources = vec![file_name.to_string()];
+    for uri in buffers.chain(images) {
+        if !uri.starts_with("data:") {
+            sources.push(relative_to(file_name, uri));
+        }
+    }
+    sources
+}
+
+struct Importer<'a> {
+    buffers: &'a [buffer::Data],
+    images: &'a [image::Data],
+    // pictures get turned into textures once however many materials use them, keyed by which
+    // picture and whether it is colour
+    textures: HashMap<(usize, bool), Option<Arc<Texture>>>,
+    // one per gltf material, in the same order
+    surfaces: Vec<Arc<dyn Surface>>,
+    scene: Scene,
+}
+
+impl Importer<'_> {
+    // everything in the file that doesnt come across the way it was meant to, so it doesnt just
+    // quietly look wrong
+    fn warn_about_losses(&mut self, document: &Document) {
+        let mut warn = |path: String, why: &str| {
+            self.scene.warnings.push(Problem {
+                location: Location::Path(path),
+                reason: Reason::Invalid(why.to_string()),
+            })
+        };
+        for mesh in document.meshes() {
+            for primitive in mesh.primitives() {
+                let why = match primitive.mode() {
+                    Mode::Points => "points have nothing to hit, so they were skipped",
+                    Mode::Lines | Mode::LineLoop | Mode::LineStrip => {
+                        "lines have nothing to hit, so they were skipped"
+                    }
+                    _ => continue,
+                };
+                warn(
+                    format!(
+                        "meshes[{}].primitives[{}].mode",
+                        mesh.index(),
+                        primitive.index()
+                    ),
+                    why,
+                );
+            }
+        }
+        for (i, data) in self.images.iter().enumerate() {
+            if matches!(
+                data.format,
+                image::Format::R32G32B32FLOAT | image::Format::R32G32B32A32FLOAT
+            ) {
+                warn(
+                    format!("images[{}]", i),
+                    "32 bit float pictures arent supported, so it was left off",
+                );
+            }
+        }
+        for light in document.lights().into_iter().flatten() {
+            let why = match light.kind() {
+                Kind::Directional => {
+                    "directional lights become the top of the sky, their direction and intensity \
+                     are left out"
+                }
+                Kind::Point => {
+                    "point lights become small white balls, their colour and intensity are left out"
+                }
+                Kind::Spot { .. } => {
+                    "spot lights are treated as point lights, small white balls without their \
+                     cone, colour or intensity"
+                }
+            };
+            warn(
+                format!("extensions.KHR_lights_punctual.lights[{}]", light.index()),
+                why,
+            );
+        }
+    }
+
+    fn add_materials(&mut self, document: &Document) {
+        for material in document.materials() {
+            let pbr = material.pbr_metallic_roughness();
+            let [r, g, b, _] = pbr.base_color_factor();
+            let mut surface = MetallicRoughness::new(
+                Rgba::from_rgb(r, g, b),
+                pbr.metallic_factor(),
+                pbr.roughness_factor(),
+                SAMPLES,
+            );
+            surface.colour_texture = pbr
+                .base_color_texture()
+                .and_then(|info| self.texture(info.texture(), true));
+            surface.metallic_roughness_texture = pbr
+                .metallic_roughness_texture()
+                .and_then(|info| self.texture(info.texture(), false));
+            let surface: Arc<dyn Surface> = Arc::new(surface);
+            let name = match material.name() {
+                Some(name) => name.to_string(),
+                None => format!("material {}", self.surfaces.len()),
+            };
+            self.scene.materials.insert(
+                name,
+                Material {
+                    surface: surface.clone(),
+                    imported: false,
+                },
+            );
+            self.surfaces.push(surface);
+        }
+    }
+
+    fn texture(&mut self, texture: texture::Texture, srgb: bool) -> Option<Arc<Texture>> {
+        let index = texture.source().index();
+        let images = self.images;
+        self.textures
+            .entry((index, srgb))
+            .or_insert_with(|| {
+                let data = images.get(index)?;
+                // 16 bit pictures keep the top half of each number, which is the second byte
+                let (channels, size) = match data.format {
+                    image::Format::R8 => (1, 1),
+                    image::Format::R8G8 => (2, 1),
+                    image::Format::R8G8B8 => (3, 1),
+                    image::Format::R8G8B8A8 => (4, 1),
+                    image::Format::R16 => (1, 2),
+                    image::Format::R16G16 => (2, 2),
+                    image::Format::R16G16B16 => (3, 2),
+                    image::Format::R16G16B16A16 => (4, 2),
+                    image::Format::R32G32B32FLOAT | image::Format::R32G32B32A32FLOAT => {
+                        return None
+                    }
+                };
+                let bytes = data
+                    .pixels
+                    .iter()
+                    .skip(size - 1)
+                    .step_by(size)
+                    .copied()
+                    .collect::<Vec<u8>>();
+                Some(Arc::new(Texture::from_bytes(
+                    data.width as usize,
+                    data.height as usize,
+                    channels,
+                    &bytes,
+                    srgb,
+                )))
+            })
+            .clone()
+    }
+
+    fn add_node(&mut self, node: &Node, parent: &Matrix4<f32>) {
+        let world = parent * Matrix4::from(node.transform().matrix());
+        let name = node.name().map(String::from);
+        if let Some(mesh) = node.mesh() {
+            self.add_mesh(
+                &mesh,
+                &world,
+                name.clone().or(mesh.name().map(String::from)),
+            );
+        }
+        if let Some(camera) = node.camera() {
+            self.add_camera(&camera, &world, name.clone());
+        }
+        if let Some(light) = node.light() {
+            let name = light.name().map(String::from).or(name);
+            match light.kind() {
+                // sunlight comes from so far away that it might as well be the sky
+                Kind::Directional => {
+                    let [r, g, b] = light.color();
+                    let sky =
+                        WorldLight::new(Rgba::from_rgb(r, g, b), WorldLight::default().bottom);
+                    self.scene.add_named(name, Arc::new(sky));
+                }
+                Kind::Point | Kind::Spot { .. } => {
+                    let position = world.transform_point(&Point3::origin()).coords;
+                    self.scene
+                        .add_named(name, Arc::new(PointLight::new(position, LIGHT_RADIUS)));
+                }
+            }
+        }
+        for child in node.children() {
+            self.add_node(&child, &world);
+        }
+    }
+
+    fn add_mesh(&mut self, mesh: &::gltf::Mesh, world: &Matrix4<f32>, name: Option<String>) {
+        // normals need the inverse transpose so that squashing a mesh doesnt tip them over
+        let normal_matrix = world
+            .fixed_view::<3, 3>(0, 0)
+            .into_owned()
+            .try_inverse()
+            .unwrap_or(Matrix3::identity())
+            .transpose();
+        for primitive in mesh.primitives() {
+            let reader = primitive.reader(|buffer| Some(&self.buffers[buffer.index()]));
+            let Some(positions) = reader.read_positions() else {
+                continue;
+            };
+            let positions = positions
+                .map(|p| world.transform_point(&Point3::from(p)).coords)
+                .collect::<Vec<Vector3<f32>>>();
+            let normals = reader.read_normals().map(|normals| {
+                normals
+                    .map(|n| (normal_matrix * Vector3::from(n)).normalize())
+                    .collect::<Vec<Vector3<f32>>>()
+            });
+            // only the first set of texture coordinates, which is all most files have
+            let uvs = reader.read_tex_coords(0).map(|uvs| {
+                uvs.into_f32()
+                    .map(Vector2::from)
+                    .collect::<Vec<Vector2<f32>>>()
+            });
+            let colours = reader.read_colors(0).map(|colours| {
+                colours
+                    .into_rgb_f32()
+                    .map(|[r, g, b]| Rgba::from_rgb(r, g, b))
+                    .collect::<Vec<Rgba>>()
+            });
+            let indices = match reader.read_indices() {
+                Some(indices) => indices.into_u32().map(|i| i as usize).collect(),
+                None => (0..positions.len()).collect::<Vec<usize>>(),
+            };
+            // lines and points have nothing to hit
+            let Some(triangles) = triangles(primitive.mode(), &indices) else {
+                continue;
+            };
+            let surface = match primitive.material().index() {
+                Some(index) => self.surfaces[index].clone(),
+                // what the gltf spec says to use when there is no material
+                None => Arc::new(MetallicRoughness::new(Rgba::WHITE, 1.0, 1.0, SAMPLES)),
+            };
+
+            for [a, b, c] in triangles {
+                if [a, b, c].iter().any(|&i| i >= positions.len()) {
+                    continue;
+                }
+                let triangle = Triangle::from_3_points_and_surface(
+                    &positions[a],
+                    &positions[b],
+                    &positions[c],
+                    surface.clone(),
+                )
+                .with_shading(Shading {
+                    normals: normals.as_ref().and_then(|n| corner_values(n, [a, b, c])),
+                    uvs: uvs.as_ref().and_then(|uv| corner_values(uv, [a, b, c])),
+                    colours: colours
+                        .as_ref()
+                        .and_then(|colours| corner_values(colours, [a, b, c])),
+                });
+                self.scene.add_named(name.clone(), Arc::new(triangle));
+            }
+        }
+    }
+
+    fn add_camera(&mut self, camera: &::gltf::Camera, world: &Matrix4<f32>, name: Option<String>) {
+        // gltf cameras look down their -z with y up
+        let position = world.transform_point(&Point3::origin()).coords;
+        let forward = (world * Vector4::new(0.0, 0.0, -1.0, 0.0))
+            .xyz()
+            .normalize();
+        let up = (world * Vector4::new(0.0, 1.0, 0.0, 0.0)).xyz();
+        let (aspect, projection) = match camera.projection() {
+            GltfProjection::Perspective(perspective) => {
+                (perspective.aspect_ratio().unwrap_or(1.0), None)
+            }
+            GltfProjection::Orthographic(orthographic) => (
+                orthographic.xmag() / orthographic.ymag(),
+                Some(Orthographic::new(2.0 * orthographic.ymag())),
+            ),
+        };
+        let width = (CAMERA_HEIGHT as f32 * aspect).round().max(1.0) as u32;
+        let mut built = Camera::look_at(position, position + forward, width, CAMERA_HEIGHT);
+        built.up = up;
+        if let GltfProjection::Perspective(perspective) = camera.projection() {
+            built.vertical_fov = perspective.yfov().to_degrees();
+        }
+        if let Some(projection) = projection {
+            built.projection = Arc::new(projection);
+        }
+        let name = camera
+            .name()
+            .map(String::from)
+            .or(name)
+            .unwrap_or(format!("camera {}", self.scene.cameras.len()));
+        self.scene.cameras.push(NamedCamera {
+            name,
+            camera: built,
+        });
+    }
+}
+
+// the corners of each triangle, however the primitive strings them together. None for the modes
+// that arent made of triangles
+fn triangles(mode: Mode, indices: &[usize]) -> Option<Vec<[usize; 3]>> {
+    let triangles = match mode {
+        Mode::Triangles => indices
+            .chunks_exact(3)
+            .map(|corners| [corners[0], corners[1], corners[2]])
+            .collect(),
+        // every other triangle gets flipped so they all face the same way
+        Mode::TriangleStrip => indices
+            .windows(3)
+            .enumerate()
+            .map(|(i, corners)| match i % 2 {
+                0 => [corners[0], corners[1], corners[2]],
+                _ => [corners[1], corners[0], corners[2]],
+            })
+            .collect(),
+        Mode::TriangleFan => indices
+            .windows(2)
+            .skip(1)
+            .map(|corners| [indices[0], corners[0], corners[1]])
+            .collect(),
+        Mode::Points | Mode::Lines | Mode::LineLoop | Mode::LineStrip => return None,
+    };
+    Some(triangles)
+}
+
+// the value at each corner, if the file has one for all three
+fn corner_values<T: Copy>(values: &[T], corners: [usize; 3]) -> Option<[T; 3]> {
+    let [a, b, c] = corners.map(|i| values.get(i).copied());
+    Some([a?, b?, c?])
+}
+
+#[cfg(test)]
+mod test {
+    use nalgebra::Vector3;
+    use serde_json::json;
+
+    use ::gltf::mesh::Mode;
+
+    use crate::{
+        renderer::Ray,
+        scene::{
+            description::SurfaceDescription, error::Location, gltf::triangles,
+            temp_folder::TempFolder, Scene,
+        },
+    };
+
+    // one triangle with normals and texture coordinates, a 2 by 1 white and black picture, a
+    // camera and a light, the way blender would write them. written three ways, with everything
+    // in the .gltf, with the buffer in a .bin next to it, and as a .glb
+    fn write_test_files(folder: &TempFolder) -> [String; 3] {
+        let buffer =
+            "AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAAAAAAAAAAAAAAAIA/AAAAAAAAAAAAAIA/\
+            AAAAAAAAAAAAAIA/AAAAAAAAAAAAAIA/AAAAAAAAAAAAAIA/";
+        let picture =
+            "iVBORw0KGgoAAAANSUhEUgAAAAIAAAABCAIAAAB7QOjdAAAAD0lEQVR4nGP4//8/AwMDAA74Av7Ji4P1\
+            AAAAAElFTkSuQmCC";
+        let contents = json!({
+            "asset": { "version": "2.0" },
+            "extensionsUsed": ["KHR_lights_punctual"],
+            "extensions": { "KHR_lights_punctual": { "lights": [{ "type": "point" }] } },
+            "scene": 0,
+            "scenes": [{ "nodes": [0, 1, 2] }],
+            "nodes": [
+                { "name": "floor", "mesh": 0, "translation": [0.0, 0.0, -2.0] },
+                { "name": "eye", "camera": 0, "translation": [0.0, 0.0, 5.0] },
+                { "name": "lamp", "translation": [0.0, 1.0, 0.0],
+                  "extensions": { "KHR_lights_punctual": { "light": 0 } } },
+            ],
+            "meshes": [{ "primitives": [{
+                "attributes": { "POSITION": 0, "NORMAL": 1, "TEXCOORD_0": 2 },
+                "material": 0,
+            }] }],
+            "materials": [{ "name": "red", "pbrMetallicRoughness": {
+                "baseColorFactor": [1.0, 0.0, 0.0, 1.0],
+                "metallicFactor": 0.0,
+                "roughnessFactor": 0.5,
+                "baseColorTexture": { "index": 0 },
+            } }],
+            "textures": [{ "source": 0 }],
+            "images": [{ "uri": format!("data:image/png;base64,{}", picture) }],
+            "cameras": [{ "type": "perspective",
+                          "perspective": { "yfov": 0.8, "aspectRatio": 1.5, "znear": 0.1 } }],
+            "accessors": [
+                { "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+                  "min": [0.0, 0.0, 0.0], "max": [1.0, 1.0, 0.0] },
+                { "bufferView": 1, "componentType": 5126, "count": 3, "type": "VEC3" },
+                { "bufferView": 2, "componentType": 5126, "count": 3, "type": "VEC2" },
+            ],
+            "bufferViews": [
+                { "buffer": 0, "byteOffset": 0, "byteLength": 36 },
+                { "buffer": 0, "byteOffset": 36, "byteLength": 36 },
+                { "buffer": 0, "byteOffset": 72, "byteLength": 24 },
+            ],
+            "buffers": [{
+                "byteLength": 96,
+                "uri": format!("data:application/octet-stream;base64,{}", buffer),
+            }],
+        });
+        let embedded = folder.write("embedded.gltf", contents.to_string());
+
+        // the same numbers as the base64 above
+        let numbers: [f32; 24] = [
+            0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, // positions
+            0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, // normals
+            0.0, 0.0, 1.0, 0.0, 0.0, 1.0, // texture coordinates
+        ];
+        let bytes = numbers
+            .iter()
+            .flat_map(|number| number.to_le_bytes())
+            .collect::<Vec<u8>>();
+        folder.write("test.bin", &bytes);
+        let mut external = contents.clone();
+        external["buffers"][0]["uri"] = json!("test.bin");
+        let external = folder.write("external.gltf", external.to_string());
+
+        // a glb is a header, then the json and the buffer each with their length and type
+        let mut binary = contents;
+        binary["buffers"][0].as_object_mut().unwrap().remove("uri");
+        let mut text = binary.to_string().into_bytes();
+        while !text.len().is_multiple_of(4) {
+            text.push(b' ');
+        }
+        let mut glb = b"glTF".to_vec();
+        let length = 12 + 8 + text.len() + 8 + bytes.len();
+        for number in [2, length as u32, text.len() as u32, 0x4E4F534A] {
+            glb.extend(number.to_le_bytes());
+        }
+        glb.extend(text);
+        for number in [bytes.len() as u32, 0x004E4942] {
+            glb.extend(number.to_le_bytes());
+        }
+        glb.extend(bytes);
+        let glb = folder.write("test.glb", glb);
+
+        [embedded, external, glb]
+    }
+
+    #[test]
+    fn gltf_files_come_in_z_up() {
+        let folder = TempFolder::new();
+        for file in write_test_files(&folder) {
+            let scene = Scene::from_gltf(&file).unwrap();
+            // the triangle was flat on the ground 2 in front in gltf, so now it stands up 2 along y
+            let hit = |x: f32, z: f32| {
+                let ray = Ray::new(Vector3::new(x, 0.0, z), Vector3::new(0.0, 1.0, 0.0));
+                scene.closest_hit(&ray)
+            };
+            let colour_at = |x: f32, z: f32| match hit(x, z).1.unwrap().describe() {
+                SurfaceDescription::MetallicRoughness(surface) => surface.colour,
+                other => panic!("expected a metallic roughness surface, got {:?}", other),
+            };
+            let result = hit(0.25, 0.25);
+            assert!((result.0.distance.unwrap() - 2.0).abs() < 1e-3);
+            assert!(result.0.normal.unwrap().direction.y.abs() > 0.999);
+            // red times the white half of the picture, then red times the black half
+            assert_eq!(colour_at(0.25, 0.25), [1.0, 0.0, 0.0]);
+            assert_eq!(colour_at(0.6, 0.2), [0.0, 0.0, 0.0]);
+            assert_eq!(hit(0.8, 0.8).0.distance, None);
+
+            assert_eq!(scene.cameras.len(), 1);
+            let camera = &scene.cameras[0];
+            assert_eq!(camera.name, "eye");
+            assert_eq!((camera.camera.width, camera.camera.height), (900, 600));
+            assert!((camera.camera.position - Vector3::new(0.0, -5.0, 0.0)).norm() < 1e-5);
+            let (forward, _, up) = camera.camera.basis();
+            assert!((forward - Vector3::new(0.0, 1.0, 0.0)).norm() < 1e-5);
+            assert!((up - Vector3::new(0.0, 0.0, 1.0)).norm() < 1e-5);
+
+            // the light is one of the objects, sitting 1 above the middle
+            let ray = Ray::new(Vector3::new(0.0, -1.0, 1.0), Vector3::new(0.0, 1.0, 0.0));
+            assert!((scene.closest_hit(&ray).0.distance.unwrap() - 1.0).abs() < 1e-3);
+            assert!(scene.materials.contains_key("red"));
+            // which lost its colour on the way
+            assert_eq!(scene.warnings.len(), 1);
+            assert_eq!(
+                scene.warnings[0].location,
+                Location::Path(String::from("extensions.KHR_lights_punctual.lights[0]"))
+            );
+        }
+    }
+
+    #[test]
+    fn strips_and_fans_become_triangles() {
+        let corners = [0, 1, 2, 3, 4];
+        assert_eq!(
+            triangles(Mode::TriangleStrip, &corners),
+            Some(vec![[0, 1, 2], [2, 1, 3], [2, 3, 4]])
+        );
+        assert_eq!(
+            triangles(Mode::TriangleFan, &corners),
+            Some(vec![[0, 1, 2], [0, 2, 3], [0, 3, 4]])
+        );
+        assert_eq!(triangles(Mode::Triangles, &corners), Some(vec![[0, 1, 2]]));
+        assert_eq!(triangles(Mode::LineStrip, &corners), None);
+    }
+}
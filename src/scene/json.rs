@@ -0,0 +1,801 @@
+use std::{fs, path::Path, sync::Arc};
+
+use epaint::Rgba;
+use nalgebra::Vector3;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    animation::Animation,
+    intersect::Intersect,
+    objects::transformed::Transformed,
+    scene::{
+        description::{
+            colour_array, CameraDescription, IncludeDescription, ItemDescription, SceneDescription,
+            SettingsDescription, SurfaceRef, TransformDescription, WorldDescription,
+        },
+        error::{Location, Problem, Reason, SceneError},
+        materials, NamedCamera, Scene,
+    },
+};
+
+impl Scene {
+    pub fn from_json(file_name: &str) -> Result<Scene, SceneError> {
+        let contents = read_json(file_name)?;
+        Scene::from_value(&contents, file_name)
+    }
+
+    // an already parsed scene file, animations use this to build each frame. file_name is just
+    // for the error messages
+    pub fn from_value(contents: &Value, file_name: &str) -> Result<Scene, SceneError> {
+        let description = SceneDescription::from_value(contents)
+            .map_err(|problems| SceneError::new(file_name, problems))?;
+        Scene::from_description(&description, file_name)
+    }
+
+    // builds everything the description asks for. by now everything in it is the right shape, so
+    // all that can be wrong is things pointing at stuff that isnt there
+    pub fn from_description(
+        description: &SceneDescription,
+        file_name: &str,
+    ) -> Result<Scene, SceneError> {
+        Scene::build_description(description, file_name, &mut vec![same_file(file_name)])
+    }
+
+    // including is every file on the way down to this one through includes, so that a file
+    // including itself gets caught rather than going round forever
+    fn build_description(
+        description: &SceneDescription,
+        file_name: &str,
+        including: &mut Vec<String>,
+    ) -> Result<Scene, SceneError> {
+        let mut problems = vec![];
+        let mut scene = Scene::new(1);
+        scene.sources.push(file_name.to_string());
+        scene.materials =
+            materials::load(description, file_name, &mut scene.sources, &mut problems);
+        scene.import_materials = description.import_materials.clone();
+        for (i, item) in description.items.iter().enumerate() {
+            match item.build(&scene.materials) {
+                Ok(object) => scene.add_named(item.name().map(String::from), object),
+                Err(reason) => problems.push(Problem {
+                    location: Location::Path(join(&index("items", i), "surface")),
+                    reason,
+                }),
+            }
+        }
+        scene.includes = description.includes.clone();
+        for (i, include) in description.includes.iter().enumerate() {
+            scene.include(
+                include,
+                file_name,
+                &index("includes", i),
+                including,
+                &mut problems,
+            );
+        }
+
+        if let Some(background) = description
+            .world
+            .as_ref()
+            .and_then(|world| world.background)
+        {
+            scene.background = Rgba::from_rgb(background[0], background[1], background[2]);
+        }
+
+        let settings = description.settings.clone().unwrap_or_default();
+        scene.settings = settings.build();
+        // either a list of named cameras or just the one
+        let cameras = match &description.camera {
+            Some(camera) if description.cameras.is_empty() => std::slice::from_ref(camera),
+            _ => description.cameras.as_slice(),
+        };
+        for (i, camera) in cameras.iter().enumerate() {
+            let path = match &description.camera {
+                Some(_) if description.cameras.is_empty() => String::from("camera"),
+                _ => index("cameras", i),
+            };
+            // without a direction to look in, or an up that isnt along it, there is no way to
+            // tell which way is right and every ray would come out as nans
+            let forward = Vector3::from(camera.target) - Vector3::from(camera.position);
+            if forward.norm() < 1e-6 {
+                problems.push(Problem {
+                    location: Location::Path(join(&path, "target")),
+                    reason: Reason::Invalid(String::from(
+                        "the camera is sat on its target so it isnt looking anywhere",
+                    )),
+                });
+            } else if let Some(up) = camera.up.map(Vector3::from) {
+                if up.norm() < 1e-6 || forward.normalize().cross(&up.normalize()).norm() < 1e-6 {
+                    problems.push(Problem {
+                        location: Location::Path(join(&path, "up")),
+                        reason: Reason::Invalid(String::from(
+                            "up has to point somewhere other than where the camera is looking",
+                        )),
+                    });
+                }
+            }
+            // objects only move between time 0 and 1, so a shutter outside that would just see
+            // them stood still at one end
+            if let Some([open, close]) = camera.shutter {
+                if !(0.0..=1.0).contains(&open) || !(0.0..=1.0).contains(&close) || open > close {
+                    problems.push(Problem {
+                        location: Location::Path(join(&path, "shutter")),
+                        reason: Reason::Invalid(format!(
+                            "the shutter should open and then close between 0 and 1, not {} to {}",
+                            open, close
+                        )),
+                    });
+                }
+            }
+            scene.cameras.push(NamedCamera {
+                name: camera.name.clone().unwrap_or(format!("camera {}", i)),
+                camera: camera.build(settings.resolution()),
+            });
+        }
+        scene.default_camera = description.default_camera.clone();
+        if let Some(name) = &scene.default_camera {
+            if scene.camera(name).is_none() {
+                problems.push(Problem {
+                    location: Location::Path(String::from("default_camera")),
+                    reason: Reason::Invalid(format!("there is no camera called {}", name)),
+                });
+            }
+        }
+
+        if let Some(animation) = &description.animation {
+            match Animation::new(animation, &description.without_animation(), file_name) {
+                Ok(animation) => scene.animation = Some(Arc::new(animation)),
+                Err(mut animation_problems) => problems.append(&mut animation_problems),
+            }
+        }
+        if !problems.is_empty() {
+            return Err(SceneError::new(file_name, problems));
+        }
+        Ok(scene)
+    }
+
+    // everything needed to build the scene again. objects that scene files cant describe yet get
+    // left out, and animated scenes are written the way they were loaded
+    pub fn describe(&self) -> SceneDescription {
+        if let Some(animation) = &self.animation {
+            return SceneDescription {
+                animation: Some(animation.describe()),
+                ..animation.base().clone()
+            };
+        }
+        // the first camera's size goes in the settings, any others that differ carry their own
+        let resolution = self
+            .default_camera()
+            .map(|camera| (camera.width, camera.height))
+            .unwrap_or((600, 600));
+        let mut settings = self.settings;
+        settings.max_depth = Some(settings.max_depth.unwrap_or(self.max_depth));
+        SceneDescription {
+            schema: None,
+            settings: Some(SettingsDescription::describe(&settings, resolution)),
+            cameras: self
+                .cameras
+                .iter()
+                .map(|named| CameraDescription::describe(&named.name, &named.camera, resolution))
+                .collect(),
+            camera: None,
+            default_camera: self.default_camera.clone(),
+            world: (self.background != Rgba::BLACK).then(|| WorldDescription {
+                background: Some(colour_array(self.background)),
+            }),
+            import_materials: self.import_materials.clone(),
+            includes: self.includes.clone(),
+            materials: self
+                .materials
+                .iter()
+                .filter(|(_, material)| !material.imported)
+                .map(|(name, material)| (name.clone(), material.surface.describe()))
+                .collect(),
+            items: self
+                .objects
+                .iter()
+                .zip(&self.names)
+                .zip(&self.included)
+                .filter(|(_, included)| !**included)
+                .filter_map(|((object, name), _)| {
+                    let mut item = object.describe()?;
+                    item.set_name(name.clone());
+                    self.use_material_names(&mut item);
+                    Some(item)
+                })
+                .collect(),
+            animation: self
+                .animation
+                .as_ref()
+                .map(|animation| animation.describe()),
+        }
+    }
+
+    // adds the items from an included file, named with its prefix and moved by its transform
+    fn include(
+        &mut self,
+        include: &IncludeDescription,
+        file_name: &str,
+        path: &str,
+        including: &mut Vec<String>,
+        problems: &mut Vec<Problem>,
+    ) {
+        let included_file = relative_to(file_name, &include.file);
+        if including.contains(&same_file(&included_file)) {
+            problems.push(Problem {
+                location: Location::Path(join(path, "file")),
+                reason: Reason::Invalid(format!("{} ends up including itself", include.file)),
+            });
+            return;
+        }
+        including.push(same_file(&included_file));
+        let included = read_json(&included_file)
+            .and_then(|contents| {
+                SceneDescription::from_value(&contents)
+                    .map_err(|problems| SceneError::new(&included_file, problems))
+            })
+            .and_then(|description| {
+                Scene::build_description(&description, &included_file, including)
+            });
+        including.pop();
+        let included = match included {
+            Ok(included) => included,
+            Err(error) => {
+                problems.append(&mut error.into_problems());
+                return;
+            }
+        };
+
+        self.sources.extend(included.sources);
+        let transform = include.transform.as_ref().map(TransformDescription::build);
+        for (object, name) in included.objects.into_iter().zip(included.names) {
+            let object: Arc<dyn Intersect> = match transform {
+                Some(transform) => Arc::new(Transformed::new(object, transform)),
+                None => object,
+            };
+            let name = match (&include.prefix, name) {
+                (Some(prefix), Some(name)) => Some(format!("{}{}", prefix, name)),
+                (_, name) => name,
+            };
+            self.add_named(name, object);
+            *self.included.last_mut().unwrap() = true;
+        }
+    }
+
+    // one object written out the way the scene file would have it, for editing. None if it isnt
+    // there or cant be written down
+    pub fn describe_item(&self, index: usize) -> Option<ItemDescription> {
+        let mut item = self.objects.get(index)?.describe()?;
+        item.set_name(self.names[index].clone());
+        self.use_material_names(&mut item);
+        Some(item)
+    }
+
+    // whether the object came in through an include, so saving writes the include instead of it
+    pub fn is_included(&self, index: usize) -> bool {
+        self.included.get(index).copied().unwrap_or(false)
+    }
+
+    // why an object cant be edited, if it cant. saving writes included objects as the include
+    // and animated scenes the way they were loaded, so an edit to either would be lost
+    pub fn why_not_editable(&self, index: usize) -> Option<&'static str> {
+        if self.is_included(index) {
+            Some("this comes from an included file, so it has to be edited there")
+        } else if self.animation.is_some() {
+            Some("the scene is animated, so it has to be edited in its file")
+        } else {
+            None
+        }
+    }
+
+    // rebuilds one object from an edited description, keeping its place in the scene. it cant
+    // use a material that isnt there, or change anything why_not_editable says no to
+    pub fn update_item(&mut self, index: usize, item: &ItemDescription) -> Result<(), Reason> {
+        if let Some(why) = self.why_not_editable(index) {
+            return Err(Reason::Invalid(String::from(why)));
+        }
+        let object = item.build(&self.materials)?;
+        self.objects[index] = object;
+        self.names[index] = item.name().map(String::from);
+        Ok(())
+    }
+
+    // surfaces that look exactly like one of the materials get written as its name, so shared
+    // surfaces stay shared after saving
+    fn use_material_names(&self, item: &mut ItemDescription) {
+        let Some(surface) = item.surface_mut() else {
+            return;
+        };
+        let Some(SurfaceRef::Surface(description)) = surface else {
+            return;
+        };
+        let found = self
+            .materials
+            .iter()
+            .find(|(_, material)| material.surface.describe() == *description);
+        if let Some((name, _)) = found {
+            *surface = Some(SurfaceRef::Material(name.clone()));
+        }
+    }
+
+    // the scene as a json scene file, which from_json loads back the same
+    pub fn to_json(&self) -> String {
+        pretty(&self.describe())
+    }
+
+    pub fn save_json(&self, file_name: &str) -> std::io::Result<()> {
+        fs::write(file_name, self.to_json())
+    }
+}
+
+// indented like the scene files in jsons/ are written by hand, with lists of numbers kept on one
+// line rather than one number per line
+pub fn pretty<T: Serialize>(value: &T) -> String {
+    let text = serde_json::to_string_pretty(value).unwrap();
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text.as_str();
+    let mut in_string = false;
+    let mut escaped = false;
+    while let Some(c) = rest.chars().next() {
+        rest = &rest[c.len_utf8()..];
+        if in_string {
+            in_string = escaped || c != '"';
+            escaped = !escaped && c == '\\';
+        } else if c == '"' {
+            in_string = true;
+        } else if c == '[' {
+            let end = rest.find(']').unwrap_or(0);
+            let inside = &rest[..end];
+            if end > 0
+                && inside
+                    .chars()
+                    .all(|c| "0123456789.-+eE,".contains(c) || c.is_whitespace())
+            {
+                let numbers = inside.split(',').map(str::trim).collect::<Vec<&str>>();
+                out.push('[');
+                out.push_str(&numbers.join(", "));
+                out.push(']');
+                rest = &rest[end + 1..];
+                continue;
+            }
+        }
+        out.push(c);
+    }
+    out + "\n"
+}
+
+// the file as json, or why it couldnt be read
+pub fn read_json(file_name: &str) -> Result<Value, SceneError> {
+    let bytes = fs::read(file_name).map_err(|error| {
+        SceneError::single(
+            file_name,
+            Location::File,
+            Reason::Unreadable(error.to_string()),
+        )
+    })?;
+    let text = String::from_utf8(bytes)
+        .map_err(|_| SceneError::single(file_name, Location::File, Reason::NotUtf8))?;
+    serde_json::from_str(&text).map_err(|error| {
+        // serde puts the position on the end of the message, but we keep that separately
+        let message = error.to_string();
+        let suffix = format!(" at line {} column {}", error.line(), error.column());
+        SceneError::single(
+            file_name,
+            Location::Line(error.line(), Some(error.column())),
+            Reason::Syntax(message.trim_end_matches(&suffix).to_string()),
+        )
+    })
+}
+
+// files other files point at are written relative to the one pointing at them
+pub fn relative_to(file_name: &str, path: &str) -> String {
+    match Path::new(file_name).parent() {
+        Some(directory) => directory.join(path).to_string_lossy().into_owned(),
+        None => path.to_string(),
+    }
+}
+
+// so that a/../b.json and b.json count as the same file
+pub fn same_file(file_name: &str) -> String {
+    match fs::canonicalize(file_name) {
+        Ok(path) => path.to_string_lossy().into_owned(),
+        Err(_) => file_name.to_string(),
+    }
+}
+
+// items[2] and surface becomes items[2].surface
+pub fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        return key.to_string();
+    }
+    format!("{}.{}", path, key)
+}
+
+pub fn index(path: &str, i: usize) -> String {
+    format!("{}[{}]", path, i)
+}
+
+#[cfg(test)]
+mod test {
+    use nalgebra::Vector3;
+    use serde_json::json;
+
+    use crate::{
+        renderer::Ray,
+        scene::{
+            description::{DiffuseDescription, ItemDescription, SurfaceDescription, SurfaceRef},
+            error::{Location, Reason},
+            temp_folder::TempFolder,
+            Scene,
+        },
+    };
+
+    #[test]
+    fn every_problem_gets_reported() {
+        let contents = json!({
+            "settings": { "sampler": "fancy" },
+            "cameras": [{ "position": [0.0, 0.0], "target": [1.0, 0.0, 0.0] }],
+            "items": [
+                { "kind": "sphere", "origin": [0.0, 0.0, 0.0] },
+                { "kind": "cube" },
+                { "kind": "triangle", "a": [0.0, 0.0, 0.0], "b": "up", "c": [0.0, 1.0, 0.0] },
+                { "kind": "quad", "a": [0.0, 0.0, 0.0], "b": [1.0, 0.0, 0.0],
+                  "c": [1.0, 1.0, 0.0], "surface": { "type": "shiny" } },
+            ],
+        });
+        let error = Scene::from_value(&contents, "broken.json").unwrap_err();
+        let found = error
+            .problems
+            .iter()
+            .map(|problem| (problem.location.clone(), problem.reason.clone()))
+            .collect::<Vec<(Location, Reason)>>();
+        let at = |path: &str| Location::Path(path.to_string());
+        assert_eq!(
+            found,
+            vec![
+                (at("items[0].radius"), Reason::Missing),
+                (
+                    at("items[1].kind"),
+                    Reason::UnknownKind {
+                        found: "cube".to_string(),
+                        expected: vec![
+                            "sphere",
+                            "triangle",
+                            "quad",
+                            "plane",
+                            "point_light",
+                            "world_light"
+                        ]
+                        .into_iter()
+                        .map(String::from)
+                        .collect()
+                    }
+                ),
+                (
+                    at("items[2].b"),
+                    Reason::WrongType {
+                        expected: "a list of numbers".to_string()
+                    }
+                ),
+                (
+                    at("items[3].surface.type"),
+                    Reason::UnknownKind {
+                        found: "shiny".to_string(),
+                        expected: vec!["specular", "diffuse", "metallic_roughness", "emissive"]
+                            .into_iter()
+                            .map(String::from)
+                            .collect()
+                    }
+                ),
+                (
+                    at("settings.sampler"),
+                    Reason::UnknownKind {
+                        found: "fancy".to_string(),
+                        expected: vec!["independent", "stratified", "halton", "sobol"]
+                            .into_iter()
+                            .map(String::from)
+                            .collect()
+                    }
+                ),
+                (
+                    at("cameras[0].position"),
+                    Reason::BadVectorLength {
+                        expected: 3,
+                        found: 2
+                    }
+                ),
+            ]
+        );
+        assert!(error
+            .to_string()
+            .starts_with("6 problems loading broken.json"));
+    }
+
+    #[test]
+    fn included_files_are_moved_and_named() {
+        let folder = TempFolder::new();
+        folder.write(
+            "pieces/ball.json",
+            json!({
+                "items": [{ "kind": "sphere", "name": "ball", "origin": [0.0, 0.0, 0.0], "radius": 1.0 }],
+            })
+            .to_string(),
+        );
+        // includes are relative to the file doing the including, not to where we are
+        folder.write(
+            "pieces/two_balls.json",
+            json!({
+                "includes": [
+                    { "file": "ball.json", "prefix": "left " },
+                    { "file": "ball.json", "prefix": "right ", "transform": { "translate": [4.0, 0.0, 0.0] } },
+                ],
+            })
+            .to_string(),
+        );
+        let scene_file = folder.write(
+            "scene.json",
+            json!({
+                "includes": [{
+                    "file": "pieces/two_balls.json",
+                    "prefix": "rig/",
+                    "transform": { "translate": [0.0, 10.0, 0.0] },
+                }],
+                "items": [{ "kind": "sphere", "name": "own", "origin": [0.0, -10.0, 0.0], "radius": 1.0 }],
+            })
+            .to_string(),
+        );
+
+        let scene = Scene::from_json(&scene_file).unwrap();
+        assert_eq!(
+            scene.names,
+            vec![
+                Some("own".to_string()),
+                Some("rig/left ball".to_string()),
+                Some("rig/right ball".to_string())
+            ]
+        );
+        let distance = |x: f32| {
+            let ray = Ray::new(Vector3::new(x, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0));
+            scene.closest_hit(&ray).0.distance
+        };
+        assert!((distance(0.0).unwrap() - 9.0).abs() < 1e-4);
+        assert!((distance(4.0).unwrap() - 9.0).abs() < 1e-4);
+        assert_eq!(distance(2.0), None);
+
+        // saving writes the include rather than copies of what it brought in
+        let saved = scene.describe();
+        assert_eq!(saved.includes.len(), 1);
+        assert_eq!(saved.items.len(), 1);
+
+        // a file that ends up including itself gets reported where the loop closes
+        folder.write(
+            "pieces/ball.json",
+            json!({ "includes": [{ "file": "two_balls.json" }] }).to_string(),
+        );
+        let error = Scene::from_json(&scene_file).unwrap_err();
+        assert_eq!(error.problems.len(), 2);
+        for problem in &error.problems {
+            assert!(
+                matches!(&problem.reason, Reason::Invalid(why) if why.contains("including itself"))
+            );
+        }
+    }
+
+    #[test]
+    fn old_triangle_colours_become_diffuse_surfaces() {
+        let triangle = json!({ "kind": "triangle", "a": [0.0, 1.0, 0.0], "b": [1.0, 1.0, 0.0],
+                               "c": [1.0, 1.0, 1.0], "colour": [0.0, 1.0, 0.0] });
+        let scene = Scene::from_value(&json!({ "items": [triangle] }), "old.json").unwrap();
+        let Some(ItemDescription::Triangle(read)) = scene.describe_item(0) else {
+            panic!("expected the triangle");
+        };
+        assert_eq!(
+            read.surface,
+            Some(SurfaceRef::Surface(SurfaceDescription::Diffuse(
+                DiffuseDescription {
+                    colour: [0.0, 1.0, 0.0],
+                    samples: 3,
+                }
+            )))
+        );
+
+        // but not as well as a surface, one of them would have to be thrown away
+        let mut both = triangle.clone();
+        both["surface"] = json!({ "type": "specular", "colour": [1.0, 1.0, 1.0] });
+        let error = Scene::from_value(&json!({ "items": [both] }), "both.json").unwrap_err();
+        assert_eq!(error.problems.len(), 1);
+        assert_eq!(
+            error.problems[0].location,
+            Location::Path(String::from("items[0].colour"))
+        );
+    }
+
+    #[test]
+    fn surfaces_without_a_type_are_the_default() {
+        // the quad in the scene the renderer has always come with has an empty surface
+        let scene = Scene::from_json("jsons/ci.json").unwrap();
+        let Some(ItemDescription::Quad(quad)) = scene.describe_item(2) else {
+            panic!("expected the quad");
+        };
+        let plain = json!({ "kind": "quad", "a": [0.0, 0.0, 2.0], "b": [1.0, 0.0, 2.0],
+                            "c": [1.0, 1.0, 2.0] });
+        let plain = Scene::from_value(&json!({ "items": [plain] }), "plain.json").unwrap();
+        let Some(ItemDescription::Quad(expected)) = plain.describe_item(0) else {
+            panic!("expected the quad");
+        };
+        assert_eq!(quad.surface, expected.surface);
+    }
+
+    #[test]
+    fn shutters_stay_between_0_and_1() {
+        let error = Scene::from_value(
+            &json!({
+                "cameras": [
+                    { "position": [0.0, 0.0, 0.0], "target": [1.0, 0.0, 0.0], "shutter": [0.0, 0.5] },
+                    { "position": [0.0, 0.0, 0.0], "target": [1.0, 0.0, 0.0], "shutter": [0.5, 2.0] },
+                    { "position": [0.0, 0.0, 0.0], "target": [1.0, 0.0, 0.0], "shutter": [0.8, 0.2] },
+                ],
+                "items": [],
+            }),
+            "shutters.json",
+        )
+        .unwrap_err();
+        let found = error
+            .problems
+            .iter()
+            .map(|problem| problem.location.clone())
+            .collect::<Vec<Location>>();
+        assert_eq!(
+            found,
+            vec![
+                Location::Path(String::from("cameras[1].shutter")),
+                Location::Path(String::from("cameras[2].shutter")),
+            ]
+        );
+    }
+
+    #[test]
+    fn cameras_have_to_look_somewhere() {
+        let error = Scene::from_value(
+            &json!({
+                "cameras": [
+                    { "position": [0.0, 0.0, 0.0], "target": [1.0, 0.0, 0.0] },
+                    { "position": [1.0, 2.0, 3.0], "target": [1.0, 2.0, 3.0] },
+                    { "position": [0.0, 0.0, 0.0], "target": [0.0, 0.0, 5.0] , "up": [0.0, 0.0, 1.0] },
+                    { "position": [0.0, 0.0, 0.0], "target": [1.0, 0.0, 0.0] , "up": [0.0, 0.0, 0.0] },
+                ],
+                "items": [],
+            }),
+            "cameras.json",
+        )
+        .unwrap_err();
+        let found = error
+            .problems
+            .iter()
+            .map(|problem| problem.location.clone())
+            .collect::<Vec<Location>>();
+        assert_eq!(
+            found,
+            vec![
+                Location::Path(String::from("cameras[1].target")),
+                Location::Path(String::from("cameras[2].up")),
+                Location::Path(String::from("cameras[3].up")),
+            ]
+        );
+
+        let error = Scene::from_value(
+            &json!({ "camera": { "position": [0.0, 0.0, 0.0], "target": [0.0, 0.0, 0.0] }, "items": [] }),
+            "camera.json",
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.problems[0].location,
+            Location::Path(String::from("camera.target"))
+        );
+    }
+
+    #[test]
+    fn picked_items_can_be_edited() {
+        let mut scene = Scene::from_value(
+            &json!({
+                "materials": { "red": { "type": "diffuse", "colour": [1.0, 0.0, 0.0], "samples": 4 } },
+                "items": [
+                    { "kind": "world_light" },
+                    { "kind": "sphere", "name": "near", "origin": [0.0, 5.0, 0.0], "radius": 1.0, "surface": "red" },
+                    { "kind": "sphere", "name": "far", "origin": [0.0, 10.0, 0.0], "radius": 1.0 },
+                ],
+            }),
+            "picking.json",
+        )
+        .unwrap();
+        let ray = Ray::new(Vector3::zeros(), Vector3::new(0.0, 1.0, 0.0));
+        assert_eq!(scene.pick(&ray), Some(1));
+        // rays that miss everything else still find the sky
+        let up = Ray::new(Vector3::zeros(), Vector3::new(0.0, 0.0, 1.0));
+        assert_eq!(scene.pick(&up), Some(0));
+
+        let Some(ItemDescription::Sphere(mut sphere)) = scene.describe_item(1) else {
+            panic!("expected the near sphere");
+        };
+        assert_eq!(sphere.name.as_deref(), Some("near"));
+        assert_eq!(
+            sphere.surface,
+            Some(SurfaceRef::Material(String::from("red")))
+        );
+        sphere.radius = 2.0;
+        scene
+            .update_item(1, &ItemDescription::Sphere(sphere.clone()))
+            .unwrap();
+        assert!((scene.closest_hit(&ray).0.distance.unwrap() - 3.0).abs() < 1e-3);
+
+        sphere.surface = Some(SurfaceRef::Material(String::from("blue")));
+        assert!(matches!(
+            scene.update_item(1, &ItemDescription::Sphere(sphere)),
+            Err(Reason::UnknownKind { .. })
+        ));
+    }
+
+    #[test]
+    fn included_items_arent_edited() {
+        let folder = TempFolder::new();
+        folder.write(
+            "ball.json",
+            json!({
+                "items": [{ "kind": "sphere", "name": "ball", "origin": [0.0, 5.0, 0.0], "radius": 1.0 }],
+            })
+            .to_string(),
+        );
+        let scene_file = folder.write(
+            "scene.json",
+            json!({ "includes": [{ "file": "ball.json" }] }).to_string(),
+        );
+
+        let mut scene = Scene::from_json(&scene_file).unwrap();
+        let Some(ItemDescription::Sphere(mut sphere)) = scene.describe_item(0) else {
+            panic!("expected the included ball");
+        };
+        assert!(scene.is_included(0));
+        sphere.radius = 2.0;
+        assert!(matches!(
+            scene.update_item(0, &ItemDescription::Sphere(sphere)),
+            Err(Reason::Invalid(why)) if why.contains("included file")
+        ));
+
+        // so what gets saved and loaded back is still what the included file says
+        scene.save_json(&scene_file).unwrap();
+        let reloaded = Scene::from_json(&scene_file).unwrap();
+        assert_eq!(reloaded.describe(), scene.describe());
+        let ray = Ray::new(Vector3::zeros(), Vector3::new(0.0, 1.0, 0.0));
+        assert!((reloaded.closest_hit(&ray).0.distance.unwrap() - 4.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn animated_scenes_arent_edited() {
+        let mut scene = Scene::from_value(
+            &json!({
+                "items": [{ "kind": "sphere", "name": "ball", "origin": [0.0, 5.0, 0.0], "radius": 1.0 }],
+                "animation": { "tracks": [{ "target": "items/ball/radius", "keys": [
+                    { "frame": 0, "value": 1.0 },
+                    { "frame": 10, "value": 3.0 },
+                ]}]},
+            }),
+            "animated.json",
+        )
+        .unwrap();
+        let before = scene.describe();
+        let Some(ItemDescription::Sphere(mut sphere)) = scene.describe_item(0) else {
+            panic!("expected the ball");
+        };
+        sphere.origin = [0.0, 8.0, 0.0];
+        assert!(matches!(
+            scene.update_item(0, &ItemDescription::Sphere(sphere)),
+            Err(Reason::Invalid(why)) if why.contains("animated")
+        ));
+
+        // saving writes the animation's own copy of the scene, which still has the ball where it was
+        assert_eq!(scene.describe(), before);
+        let ray = Ray::new(Vector3::zeros(), Vector3::new(0.0, 1.0, 0.0));
+        assert!((scene.closest_hit(&ray).0.distance.unwrap() - 4.0).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,234 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use crate::{
+    scene::{
+        description::{LibraryDescription, SceneDescription},
+        error::{Location, Problem, Reason, SceneError},
+        json::{index, read_json, relative_to, same_file},
+    },
+    surfaces::Surface,
+};
+
+// a named surface from a scene file. everything that uses it shares the one surface
+#[derive(Clone, Debug)]
+pub struct Material {
+    pub surface: Arc<dyn Surface>,
+    // came from one of the import_materials files, so saving the scene leaves it in there
+    pub imported: bool,
+}
+
+// every material the scene file can use, from its imports first and then its own. whatever
+// could be loaded is still returned when there are problems, so items using it dont get
+// reported too. the libraries it reads get added to sources
+pub fn load(
+    description: &SceneDescription,
+    file_name: &str,
+    sources: &mut Vec<String>,
+    problems: &mut Vec<Problem>,
+) -> BTreeMap<String, Material> {
+    let mut materials = BTreeMap::new();
+    let mut importing = vec![same_file(file_name)];
+    for (i, import) in description.import_materials.iter().enumerate() {
+        import_library(
+            &relative_to(file_name, import),
+            Location::Path(index("import_materials", i)),
+            &mut importing,
+            &mut materials,
+            sources,
+            problems,
+        );
+    }
+    for (name, surface) in &description.materials {
+        materials.insert(
+            name.clone(),
+            Material {
+                surface: surface.build(),
+                imported: false,
+            },
+        );
+    }
+    materials
+}
+
+// location is where the import was asked for, importing is the chain of files that led here so
+// that a file importing itself doesnt go round forever
+fn import_library(
+    file_name: &str,
+    location: Location,
+    importing: &mut Vec<String>,
+    materials: &mut BTreeMap<String, Material>,
+    sources: &mut Vec<String>,
+    problems: &mut Vec<Problem>,
+) {
+    if importing.contains(&same_file(file_name)) {
+        problems.push(Problem {
+            location,
+            reason: Reason::Invalid(format!("{} ends up importing itself", file_name)),
+        });
+        return;
+    }
+    let library = read_json(file_name).and_then(|contents| {
+        LibraryDescription::from_value(&contents)
+            .map_err(|problems| SceneError::new(file_name, problems))
+    });
+    let library = match library {
+        Ok(library) => library,
+        Err(error) => {
+            problems.append(&mut error.into_problems());
+            return;
+        }
+    };
+    sources.push(file_name.to_string());
+
+    importing.push(same_file(file_name));
+    for (i, nested) in library.import_materials.iter().enumerate() {
+        import_library(
+            &relative_to(file_name, nested),
+            Location::InFile(
+                file_name.to_string(),
+                Box::new(Location::Path(index("import_materials", i))),
+            ),
+            importing,
+            materials,
+            sources,
+            problems,
+        );
+    }
+    importing.pop();
+    for (name, surface) in &library.materials {
+        materials.insert(
+            name.clone(),
+            Material {
+                surface: surface.build(),
+                imported: true,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use serde_json::json;
+
+    use crate::scene::{
+        error::{Location, Reason},
+        temp_folder::TempFolder,
+        Scene,
+    };
+
+    fn plane(surface: serde_json::Value) -> serde_json::Value {
+        json!({
+            "kind": "plane",
+            "a": [0.0, 0.0, 0.0],
+            "b": [1.0, 0.0, 0.0],
+            "c": [0.0, 1.0, 0.0],
+            "surface": surface,
+        })
+    }
+
+    #[test]
+    fn items_share_one_surface_per_material() {
+        let contents = json!({
+            "materials": { "red": { "type": "diffuse", "colour": [1.0, 0.0, 0.0], "samples": 4 } },
+            "items": [plane(json!("red")), plane(json!("red"))],
+        });
+        let scene = Scene::from_value(&contents, "shared.json").unwrap();
+        // one for the table and one for each plane
+        assert_eq!(Arc::strong_count(&scene.materials["red"].surface), 3);
+
+        let saved = scene.describe();
+        assert_eq!(saved.materials.len(), 1);
+        let loaded = Scene::from_description(&saved, "shared.json").unwrap();
+        assert_eq!(Arc::strong_count(&loaded.materials["red"].surface), 3);
+    }
+
+    #[test]
+    fn materials_can_be_imported() {
+        let folder = TempFolder::new();
+        folder.write(
+            "library/metals.json",
+            json!({
+                "import_materials": ["paints.json"],
+                "materials": { "mirror": { "type": "specular", "colour": [1.0, 1.0, 1.0] } },
+            })
+            .to_string(),
+        );
+        folder.write(
+            "library/paints.json",
+            json!({
+                "materials": {
+                    "blue": { "type": "diffuse", "colour": [0.0, 0.0, 1.0], "samples": 1 },
+                    "mirror": { "type": "diffuse", "colour": [0.0, 0.0, 0.0], "samples": 1 },
+                },
+            })
+            .to_string(),
+        );
+        let scene_file = folder.write(
+            "scene.json",
+            json!({
+                "import_materials": ["library/metals.json"],
+                "materials": { "blue": { "type": "specular", "colour": [0.0, 0.0, 1.0] } },
+                "items": [plane(json!("mirror")), plane(json!("blue"))],
+            })
+            .to_string(),
+        );
+
+        let scene = Scene::from_json(&scene_file).unwrap();
+        let names = scene.materials.keys().cloned().collect::<Vec<String>>();
+        assert_eq!(names, vec!["blue", "mirror"]);
+        // later files win over the ones they import, and the scene's own win over everything
+        assert!(scene.materials["mirror"].imported);
+        assert!(!scene.materials["blue"].imported);
+        assert_eq!(
+            serde_json::to_value(scene.materials["mirror"].surface.describe()).unwrap()["type"],
+            "specular"
+        );
+        assert_eq!(
+            serde_json::to_value(scene.materials["blue"].surface.describe()).unwrap()["type"],
+            "specular"
+        );
+
+        // only the scene's own materials get written back out
+        let saved = scene.describe();
+        assert_eq!(saved.import_materials, vec!["library/metals.json"]);
+        assert_eq!(
+            saved.materials.keys().collect::<Vec<&String>>(),
+            vec!["blue"]
+        );
+
+        // and a library pulling itself back in gets reported rather than looping
+        folder.write(
+            "library/paints.json",
+            json!({ "import_materials": ["metals.json"] }).to_string(),
+        );
+        let error = Scene::from_json(&scene_file).unwrap_err();
+        assert_eq!(error.problems.len(), 1);
+        assert!(matches!(
+            &error.problems[0].location,
+            Location::InFile(file, _) if file.ends_with("paints.json")
+        ));
+    }
+
+    #[test]
+    fn unknown_materials_are_reported() {
+        let contents = json!({
+            "materials": { "red": { "type": "specular", "colour": [1.0, 0.0, 0.0] } },
+            "items": [plane(json!("red")), plane(json!("green"))],
+        });
+        let error = Scene::from_value(&contents, "missing.json").unwrap_err();
+        assert_eq!(error.problems.len(), 1);
+        assert_eq!(
+            error.problems[0].location,
+            Location::Path("items[1].surface".to_string())
+        );
+        assert_eq!(
+            error.problems[0].reason,
+            Reason::UnknownKind {
+                found: "green".to_string(),
+                expected: vec!["red".to_string()]
+            }
+        );
+    }
+}
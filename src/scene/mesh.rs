@@ -0,0 +1,111 @@
+use std::sync::Arc;
+
+use epaint::Rgba;
+use nalgebra::{Matrix3, Matrix4, Point3, Vector2, Vector3};
+
+use crate::{
+    objects::triangle::{Shading, Triangle},
+    scene::error::{Location, Problem, Reason},
+    surfaces::Surface,
+};
+
+// corners and the faces between them, the way mesh files store them before they get turned into
+// triangles
+#[derive(Clone, Debug, Default)]
+pub struct Mesh {
+    pub vertices: Vec<Vertex>,
+    // indices into vertices, any number of corners going round the edge
+    pub faces: Vec<Vec<usize>>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Vertex {
+    pub position: Vector3<f32>,
+    pub normal: Option<Vector3<f32>>,
+    pub uv: Option<Vector2<f32>>,
+    pub colour: Option<Rgba>,
+}
+
+impl Vertex {
+    pub fn at(position: Vector3<f32>) -> Vertex {
+        Vertex {
+            position,
+            normal: None,
+            uv: None,
+            colour: None,
+        }
+    }
+}
+
+impl Mesh {
+    // moves every vertex, normals use the inverse transpose so squashing the mesh doesnt tip
+    // them over
+    pub fn transform(&mut self, matrix: &Matrix4<f32>) {
+        let normal_matrix = matrix
+            .fixed_view::<3, 3>(0, 0)
+            .into_owned()
+            .try_inverse()
+            .unwrap_or(Matrix3::identity())
+            .transpose();
+        for vertex in &mut self.vertices {
+            vertex.position = matrix
+                .transform_point(&Point3::from(vertex.position))
+                .coords;
+            vertex.normal = vertex
+                .normal
+                .map(|normal| (normal_matrix * normal).normalize());
+        }
+    }
+
+    // faces with more than three corners get fanned out from their first one. faces pointing at
+    // vertices that arent there get reported where face is the name for them in the file
+    pub fn triangles(
+        &self,
+        surface: &Arc<dyn Surface>,
+        face: &str,
+    ) -> Result<Vec<Triangle>, Vec<Problem>> {
+        let mut triangles = vec![];
+        let mut problems = vec![];
+        for (number, corners) in self.faces.iter().enumerate() {
+            if let Some(&corner) = corners
+                .iter()
+                .find(|&&corner| corner >= self.vertices.len())
+            {
+                problems.push(Problem {
+                    location: Location::Path(format!("{} {}", face, number)),
+                    reason: Reason::Invalid(format!(
+                        "corner {} is past the {} vertices",
+                        corner,
+                        self.vertices.len()
+                    )),
+                });
+                continue;
+            }
+            for i in 1..corners.len().saturating_sub(1) {
+                let [a, b, c] = [corners[0], corners[i], corners[i + 1]].map(|i| self.vertices[i]);
+                let triangle = Triangle::from_3_points_and_surface(
+                    &a.position,
+                    &b.position,
+                    &c.position,
+                    surface.clone(),
+                )
+                .with_shading(Shading {
+                    normals: all_three([a.normal, b.normal, c.normal]),
+                    uvs: all_three([a.uv, b.uv, c.uv]),
+                    colours: all_three([a.colour, b.colour, c.colour]),
+                });
+                triangles.push(triangle);
+            }
+        }
+        if !problems.is_empty() {
+            return Err(problems);
+        }
+        Ok(triangles)
+    }
+}
+
+// only if every corner has one
+fn all_three<T>(values: [Option<T>; 3]) -> Option<[T; 3]> {
+    let [a, b, c] = values;
+    Some([a?, b?, c?])
+}
@@ -0,0 +1,293 @@
+use std::{collections::HashMap, fs, sync::Arc};
+
+use epaint::Rgba;
+use nalgebra::{Matrix4, Vector2, Vector3};
+
+use crate::{
+    scene::{
+        error::{Location, Problem, Reason, SceneError},
+        mesh::{Mesh, Vertex},
+        Scene, DEPTH,
+    },
+    surfaces::{diffuse::Diffuse, Surface},
+};
+
+// how many rays the mesh's surface sends off when something hits it
+const SAMPLES: usize = 3;
+
+impl Scene {
+    // a wavefront .obj mesh, with its normals and texture coordinates. materials arent read, the
+    // whole thing is white and a warning says so. objs almost always have y pointing up, so it
+    // gets turned to have z up like everything else here
+    pub fn from_obj(file_name: &str) -> Result<Scene, SceneError> {
+        let (mut mesh, warnings) = read_obj(file_name)?;
+        let y_up_to_z_up = Matrix4::new(
+            1.0, 0.0, 0.0, 0.0, //
+            0.0, 0.0, -1.0, 0.0, //
+            0.0, 1.0, 0.0, 0.0, //
+            0.0, 0.0, 0.0, 1.0,
+        );
+        mesh.transform(&y_up_to_z_up);
+
+        let mut scene = Scene::new(DEPTH);
+        scene.sources.push(file_name.to_string());
+        scene.warnings = warnings;
+        let surface: Arc<dyn Surface> = Arc::new(Diffuse::new(Rgba::WHITE, SAMPLES));
+        let triangles = mesh
+            .triangles(&surface, "face")
+            .map_err(|problems| SceneError::new(file_name, problems))?;
+        for triangle in triangles {
+            scene.add(Arc::new(triangle));
+        }
+        Ok(scene)
+    }
+}
+
+// the vertices and faces in an obj file as it has them, along with warnings about the lines that
+// got skipped. corners that share a position but not a normal or texture coordinate become
+// separate vertices
+pub fn read_obj(file_name: &str) -> Result<(Mesh, Vec<Problem>), SceneError> {
+    let bytes = fs::read(file_name).map_err(|error| {
+        SceneError::single(
+            file_name,
+            Location::File,
+            Reason::Unreadable(error.to_string()),
+        )
+    })?;
+    let text = String::from_utf8(bytes)
+        .map_err(|_| SceneError::single(file_name, Location::File, Reason::NotUtf8))?;
+
+    let mut positions: Vec<(Vector3<f32>, Option<Rgba>)> = vec![];
+    let mut uvs: Vec<Vector2<f32>> = vec![];
+    let mut normals: Vec<Vector3<f32>> = vec![];
+    let mut mesh = Mesh::default();
+    // which vertex each position/uv/normal combination already became
+    let mut corners: HashMap<(usize, Option<usize>, Option<usize>), usize> = HashMap::new();
+    let mut problems = vec![];
+    let mut warnings = vec![];
+    let mut warned_materials = false;
+
+    for (number, line) in text.lines().enumerate() {
+        let location = Location::Line(number + 1, None);
+        let words = line.split_whitespace().collect::<Vec<&str>>();
+        let Some((&statement, values)) = words.split_first() else {
+            continue;
+        };
+        let numbers = || {
+            values
+                .iter()
+                .map(|value| {
+                    value
+                        .parse::<f32>()
+                        .map_err(|_| Reason::BadNumber(value.to_string()))
+                })
+                .collect::<Result<Vec<f32>, Reason>>()
+        };
+        let result = match statement {
+            // some exporters put a colour after the position
+            "v" => numbers().and_then(|numbers| match numbers.as_slice() {
+                [x, y, z] | [x, y, z, _] => {
+                    positions.push((Vector3::new(*x, *y, *z), None));
+                    Ok(())
+                }
+                [x, y, z, r, g, b] => {
+                    positions.push((Vector3::new(*x, *y, *z), Some(Rgba::from_rgb(*r, *g, *b))));
+                    Ok(())
+                }
+                _ => Err(Reason::BadVectorLength {
+                    expected: 3,
+                    found: numbers.len(),
+                }),
+            }),
+            "vt" => numbers().and_then(|numbers| match numbers.as_slice() {
+                [u] => {
+                    uvs.push(Vector2::new(*u, 0.0));
+                    Ok(())
+                }
+                [u, v, ..] => {
+                    uvs.push(Vector2::new(*u, *v));
+                    Ok(())
+                }
+                [] => Err(Reason::BadVectorLength {
+                    expected: 2,
+                    found: 0,
+                }),
+            }),
+            "vn" => numbers().and_then(|numbers| match numbers.as_slice() {
+                [x, y, z] => {
+                    normals.push(Vector3::new(*x, *y, *z));
+                    Ok(())
+                }
+                _ => Err(Reason::BadVectorLength {
+                    expected: 3,
+                    found: numbers.len(),
+                }),
+            }),
+            "f" => {
+                let mut face = vec![];
+                let mut result = Ok(());
+                for value in values {
+                    match read_corner(value, positions.len(), uvs.len(), normals.len()) {
+                        Ok(corner) => {
+                            let next = mesh.vertices.len();
+                            let vertex = *corners.entry(corner).or_insert(next);
+                            if vertex == next {
+                                let (position, colour) = positions[corner.0];
+                                let mut vertex = Vertex::at(position);
+                                vertex.colour = colour;
+                                vertex.uv = corner.1.map(|uv| uvs[uv]);
+                                vertex.normal = corner.2.map(|normal| normals[normal]);
+                                mesh.vertices.push(vertex);
+                            }
+                            face.push(vertex);
+                        }
+                        Err(reason) => {
+                            result = Err(reason);
+                            break;
+                        }
+                    }
+                }
+                if result.is_ok() {
+                    mesh.faces.push(face);
+                }
+                result
+            }
+            // groups, objects and smoothing dont change what gets drawn
+            "o" | "g" | "s" => Ok(()),
+            "mtllib" | "usemtl" => {
+                if !warned_materials {
+                    warned_materials = true;
+                    warnings.push(Problem {
+                        location: location.clone(),
+                        reason: Reason::Invalid(String::from(
+                            "obj materials arent read, so everything is white",
+                        )),
+                    });
+                }
+                Ok(())
+            }
+            _ if statement.starts_with('#') => Ok(()),
+            _ => {
+                warnings.push(Problem {
+                    location: location.clone(),
+                    reason: Reason::Invalid(format!(
+                        "{} isnt supported, so it was skipped",
+                        statement
+                    )),
+                });
+                Ok(())
+            }
+        };
+        if let Err(reason) = result {
+            problems.push(Problem { location, reason });
+        }
+    }
+
+    if !problems.is_empty() {
+        return Err(SceneError::new(file_name, problems));
+    }
+    Ok((mesh, warnings))
+}
+
+// a face's corner, written position/uv/normal with the last two optional. indices start at 1, or
+// count back from the end if they are negative
+fn read_corner(
+    value: &str,
+    positions: usize,
+    uvs: usize,
+    normals: usize,
+) -> Result<(usize, Option<usize>, Option<usize>), Reason> {
+    let mut parts = value.split('/');
+    let mut index = |count: usize, needed: bool| -> Result<Option<usize>, Reason> {
+        let part = match parts.next() {
+            Some("") | None if !needed => return Ok(None),
+            part => part.unwrap_or(""),
+        };
+        let index = part
+            .parse::<i64>()
+            .map_err(|_| Reason::BadNumber(part.to_string()))?;
+        let found = match index {
+            index if index > 0 => index - 1,
+            index => count as i64 + index,
+        };
+        if found < 0 || found >= count as i64 {
+            return Err(Reason::Invalid(format!(
+                "{} is past the {} there are",
+                index, count
+            )));
+        }
+        Ok(Some(found as usize))
+    };
+    let position = index(positions, true)?.unwrap();
+    Ok((position, index(uvs, false)?, index(normals, false)?))
+}
+
+#[cfg(test)]
+mod test {
+    use nalgebra::{Vector2, Vector3};
+
+    use crate::{
+        renderer::Ray,
+        scene::{
+            error::{Location, Reason},
+            obj::read_obj,
+            temp_folder::TempFolder,
+            Scene,
+        },
+    };
+
+    #[test]
+    fn obj_files_come_in_z_up() {
+        let folder = TempFolder::new();
+        let file = folder.write(
+            "square.obj",
+            "# a square lying flat a little way up, facing up in y\n\
+             mtllib square.mtl\n\
+             o square\n\
+             v 0 1 0\nv 1 1 0\nv 1 1 -1\nv 0 1 -1\n\
+             vt 0 0\nvt 1 0\nvt 1 1\nvt 0 1\n\
+             vn 0 1 0\n\
+             usemtl white\n\
+             f 1/1/1 2/2/1 3/3/1 -1/-1/-1\n",
+        );
+        let (mesh, warnings) = read_obj(&file).unwrap();
+        assert_eq!(mesh.faces, vec![vec![0, 1, 2, 3]]);
+        assert_eq!(mesh.vertices[3].uv, Some(Vector2::new(0.0, 1.0)));
+        // materials only get warned about once
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].location, Location::Line(2, None));
+
+        let scene = Scene::from_obj(&file).unwrap();
+        assert_eq!(scene.objects().len(), 2);
+        // -z in the file is +y once z is up, and the square faces up in z
+        let ray = Ray::new(Vector3::new(0.3, 0.6, 5.0), Vector3::new(0.0, 0.0, -1.0));
+        let hit = scene.closest_hit(&ray).0;
+        assert!((hit.distance.unwrap() - 4.0).abs() < 1e-3);
+        assert!((hit.normal.unwrap().direction - Vector3::z()).norm() < 1e-3);
+    }
+
+    #[test]
+    fn obj_problems_have_line_numbers() {
+        let folder = TempFolder::new();
+        let file = folder.write("broken.obj", "v 0 0 0\nv 1 0 zero\nv 0 1 0\nf 1 2 4\n");
+        let error = Scene::from_obj(&file).unwrap_err();
+        let found = error
+            .problems
+            .iter()
+            .map(|problem| (problem.location.clone(), problem.reason.clone()))
+            .collect::<Vec<(Location, Reason)>>();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    Location::Line(2, None),
+                    Reason::BadNumber(String::from("zero"))
+                ),
+                (
+                    Location::Line(4, None),
+                    Reason::Invalid(String::from("4 is past the 2 there are"))
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,997 @@
+use std::{collections::HashMap, fs, sync::Arc};
+
+use epaint::Rgba;
+use nalgebra::{Matrix4, Point3, Rotation3, Unit, Vector3, Vector4};
+
+use crate::{
+    camera::Camera,
+    filter::FilterKind,
+    objects::{light::PointLight, sphere::Sphere, world_light::WorldLight},
+    projection::{equirectangular::Equirectangular, orthographic::Orthographic},
+    sampler::SamplerKind,
+    scene::{
+        error::{Location, Problem, Reason, SceneError},
+        json::relative_to,
+        materials::Material,
+        mesh::{Mesh, Vertex},
+        ply::read_ply,
+        NamedCamera, Scene, DEPTH,
+    },
+    surfaces::{
+        diffuse::Diffuse, emissive::Emissive, metallic_roughness::MetallicRoughness,
+        specular::Specular, Surface,
+    },
+};
+
+// how many rays matte and rough surfaces send off when something hits them
+const SAMPLES: usize = 3;
+
+// pbrt point lights are just points, but ours need a size to be seen
+const LIGHT_RADIUS: f32 = 0.1;
+
+// the types that start a "type name" parameter
+const PARAMETER_TYPES: [&str; 18] = [
+    "integer",
+    "float",
+    "point",
+    "point2",
+    "point3",
+    "vector",
+    "vector2",
+    "vector3",
+    "normal",
+    "normal3",
+    "color",
+    "rgb",
+    "spectrum",
+    "xyz",
+    "blackbody",
+    "string",
+    "bool",
+    "texture",
+];
+
+impl Scene {
+    // the parts of a pbrt-v3 file that map onto this renderer: the camera, film, sampler and
+    // filter, spheres, triangle meshes and ply meshes, matte, mirror, glass and metal materials,
+    // and area, point and infinite lights. anything else gets left out with a warning in
+    // scene.warnings, so renders can be compared knowing what is missing
+    pub fn from_pbrt(file_name: &str) -> Result<Scene, SceneError> {
+        let bytes = fs::read(file_name).map_err(|error| {
+            SceneError::single(
+                file_name,
+                Location::File,
+                Reason::Unreadable(error.to_string()),
+            )
+        })?;
+        let text = String::from_utf8(bytes)
+            .map_err(|_| SceneError::single(file_name, Location::File, Reason::NotUtf8))?;
+        let directives =
+            parse(&text).map_err(|problem| SceneError::new(file_name, vec![problem]))?;
+
+        let mut loader = Loader::new(file_name);
+        loader.scene.sources.push(file_name.to_string());
+        for directive in &directives {
+            loader.directive(directive);
+        }
+        if !loader.in_world {
+            loader.world_begin();
+        }
+        if !loader.problems.is_empty() {
+            return Err(SceneError::new(file_name, loader.problems));
+        }
+        loader.scene.warnings = loader.warnings;
+        Ok(loader.scene)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Text(String),
+    Number(f64),
+    Open,
+    Close,
+}
+
+// a value written after a directive or in a parameter's list
+#[derive(Clone, Debug, PartialEq)]
+enum Arg {
+    Number(f64),
+    Text(String),
+}
+
+#[derive(Clone, Debug)]
+struct Param {
+    kind: String,
+    name: String,
+    values: Vec<Arg>,
+}
+
+// one line of the file, like Shape "sphere" "float radius" [2]
+#[derive(Clone, Debug)]
+struct Directive {
+    name: String,
+    line: usize,
+    args: Vec<Arg>,
+    params: Vec<Param>,
+}
+
+impl Directive {
+    fn param(&self, name: &str) -> Option<&Param> {
+        self.params.iter().find(|param| param.name == name)
+    }
+
+    fn numbers(&self, name: &str) -> Option<Vec<f32>> {
+        let numbers = self
+            .param(name)?
+            .values
+            .iter()
+            .filter_map(|value| match value {
+                Arg::Number(number) => Some(*number as f32),
+                Arg::Text(_) => None,
+            })
+            .collect();
+        Some(numbers)
+    }
+
+    fn number(&self, name: &str) -> Option<f32> {
+        self.numbers(name)?.first().copied()
+    }
+
+    fn text(&self, name: &str) -> Option<&str> {
+        match self.param(name)?.values.first()? {
+            Arg::Text(text) => Some(text),
+            Arg::Number(_) => None,
+        }
+    }
+
+    // the first thing written after the directive's name, which says what kind it is
+    fn kind(&self) -> &str {
+        match self.args.first() {
+            Some(Arg::Text(text)) => text,
+            _ => "",
+        }
+    }
+
+    fn positional_numbers(&self) -> Vec<f32> {
+        self.args
+            .iter()
+            .filter_map(|arg| match arg {
+                Arg::Number(number) => Some(*number as f32),
+                Arg::Text(_) => None,
+            })
+            .collect()
+    }
+}
+
+fn tokenise(text: &str) -> Result<Vec<(usize, Token)>, Problem> {
+    let mut tokens = vec![];
+    let mut line = 1;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            c if c.is_whitespace() => {}
+            '#' => while chars.next_if(|&c| c != '\n').is_some() {},
+            '[' => tokens.push((line, Token::Open)),
+            ']' => tokens.push((line, Token::Close)),
+            '"' => {
+                let mut string = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\n') | None => {
+                            return Err(Problem {
+                                location: Location::Line(line, None),
+                                reason: Reason::Syntax(String::from("text without an end quote")),
+                            })
+                        }
+                        Some(c) => string.push(c),
+                    }
+                }
+                tokens.push((line, Token::Text(string)));
+            }
+            c => {
+                let mut word = String::from(c);
+                while let Some(c) = chars.next_if(|&c| !c.is_whitespace() && !"[]\"#".contains(c)) {
+                    word.push(c);
+                }
+                tokens.push((
+                    line,
+                    match word.parse() {
+                        Ok(number) => Token::Number(number),
+                        Err(_) => Token::Word(word),
+                    },
+                ));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+fn parse(text: &str) -> Result<Vec<Directive>, Problem> {
+    let tokens = tokenise(text)?;
+    let mut directives: Vec<Directive> = vec![];
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some((line, token)) = tokens.next() {
+        let problem = |why: &str| Problem {
+            location: Location::Line(line, None),
+            reason: Reason::Syntax(why.to_string()),
+        };
+        let Token::Word(name) = token else {
+            return Err(problem("expected a directive like Shape or Camera"));
+        };
+        let mut directive = Directive {
+            name,
+            line,
+            args: vec![],
+            params: vec![],
+        };
+        // everything up to the next directive belongs to this one
+        while let Some((_, token)) = tokens.next_if(|(_, token)| !matches!(token, Token::Word(_))) {
+            match token {
+                Token::Text(text) if is_parameter(&text) => {
+                    let (kind, name) = text.trim().split_once(char::is_whitespace).unwrap();
+                    let values = match tokens.next() {
+                        Some((_, Token::Open)) => {
+                            let mut values = vec![];
+                            loop {
+                                match tokens.next() {
+                                    Some((_, Token::Close)) => break,
+                                    Some((_, Token::Number(number))) => {
+                                        values.push(Arg::Number(number))
+                                    }
+                                    Some((_, Token::Text(text))) => values.push(Arg::Text(text)),
+                                    // bare true and false show up in some exporters' lists
+                                    Some((_, Token::Word(word))) => values.push(Arg::Text(word)),
+                                    _ => return Err(problem("a list without a ]")),
+                                }
+                            }
+                            values
+                        }
+                        Some((_, Token::Number(number))) => vec![Arg::Number(number)],
+                        Some((_, Token::Text(text))) => vec![Arg::Text(text)],
+                        _ => return Err(problem(&format!("{} has no value", text))),
+                    };
+                    directive.params.push(Param {
+                        kind: kind.to_string(),
+                        name: name.trim().to_string(),
+                        values,
+                    });
+                }
+                Token::Text(text) => directive.args.push(Arg::Text(text)),
+                Token::Number(number) => directive.args.push(Arg::Number(number)),
+                // lists of plain numbers, like Transform [...]
+                Token::Open => loop {
+                    match tokens.next() {
+                        Some((_, Token::Close)) => break,
+                        Some((_, Token::Number(number))) => {
+                            directive.args.push(Arg::Number(number))
+                        }
+                        Some((_, Token::Text(text))) => directive.args.push(Arg::Text(text)),
+                        _ => return Err(problem("a list without a ]")),
+                    }
+                },
+                Token::Close => return Err(problem("a ] without a [")),
+                Token::Word(_) => unreachable!(),
+            }
+        }
+        directives.push(directive);
+    }
+    Ok(directives)
+}
+
+fn is_parameter(text: &str) -> bool {
+    let words = text.split_whitespace().collect::<Vec<&str>>();
+    words.len() == 2 && PARAMETER_TYPES.contains(&words[0])
+}
+
+// what AttributeBegin saves and AttributeEnd puts back
+#[derive(Clone, Debug)]
+struct State {
+    transform: Matrix4<f32>,
+    material: Arc<dyn Surface>,
+    // shapes glow this colour rather than using the material
+    area_light: Option<Rgba>,
+}
+
+struct Loader<'a> {
+    file_name: &'a str,
+    scene: Scene,
+    problems: Vec<Problem>,
+    warnings: Vec<Problem>,
+    state: State,
+    attributes: Vec<State>,
+    transforms: Vec<Matrix4<f32>>,
+    named_materials: HashMap<String, Arc<dyn Surface>>,
+    // the camera's directive and where it is, built once the film size is known
+    camera: Option<(Directive, Matrix4<f32>)>,
+    film: (u32, u32),
+    // pbrt is left handed, so if its camera would see the world the other way round to ours the
+    // whole scene gets mirrored to make the picture come out the same way round
+    mirror: Matrix4<f32>,
+    in_world: bool,
+    // shapes between ObjectBegin and ObjectEnd are only used through instances, which we dont do
+    in_object: bool,
+}
+
+impl Loader<'_> {
+    fn new(file_name: &str) -> Loader<'_> {
+        Loader {
+            file_name,
+            scene: Scene::new(DEPTH),
+            problems: vec![],
+            warnings: vec![],
+            state: State {
+                transform: Matrix4::identity(),
+                // pbrt's default material
+                material: Arc::new(Diffuse::new(Rgba::from_gray(0.5), SAMPLES)),
+                area_light: None,
+            },
+            attributes: vec![],
+            transforms: vec![],
+            named_materials: HashMap::new(),
+            camera: None,
+            film: (640, 480),
+            mirror: Matrix4::identity(),
+            in_world: false,
+            in_object: false,
+        }
+    }
+
+    fn warn(&mut self, directive: &Directive, why: String) {
+        self.warnings.push(Problem {
+            location: Location::Line(directive.line, None),
+            reason: Reason::Invalid(why),
+        });
+    }
+
+    fn unsupported(&mut self, directive: &Directive) {
+        let why = match directive.kind() {
+            "" => format!("{} isnt supported, so it was left out", directive.name),
+            kind => format!(
+                "{} {} isnt supported, so it was left out",
+                directive.name, kind
+            ),
+        };
+        self.warn(directive, why);
+    }
+
+    fn directive(&mut self, directive: &Directive) {
+        let numbers = directive.positional_numbers();
+        let transform = match directive.name.as_str() {
+            "Identity" => Some(Matrix4::identity()),
+            "Translate" => self.numbers(directive, &numbers, 3).map(|n| {
+                self.state.transform * Matrix4::new_translation(&Vector3::new(n[0], n[1], n[2]))
+            }),
+            "Scale" => self.numbers(directive, &numbers, 3).map(|n| {
+                self.state.transform
+                    * Matrix4::new_nonuniform_scaling(&Vector3::new(n[0], n[1], n[2]))
+            }),
+            "Rotate" => self.numbers(directive, &numbers, 4).and_then(|n| {
+                // a zero axis would fill the whole transform with nans
+                let Some(axis) = Unit::try_new(Vector3::new(n[1], n[2], n[3]), f32::EPSILON) else {
+                    self.problems.push(Problem {
+                        location: Location::Line(directive.line, None),
+                        reason: Reason::Invalid(String::from(
+                            "Rotate needs an axis to turn around",
+                        )),
+                    });
+                    return None;
+                };
+                let rotation = Rotation3::from_axis_angle(&axis, n[0].to_radians());
+                Some(self.state.transform * rotation.to_homogeneous())
+            }),
+            "LookAt" => self
+                .numbers(directive, &numbers, 9)
+                .map(|n| self.state.transform * look_at(&n)),
+            "Transform" => self
+                .numbers(directive, &numbers, 16)
+                .map(|n| Matrix4::from_column_slice(&n)),
+            "ConcatTransform" => self
+                .numbers(directive, &numbers, 16)
+                .map(|n| self.state.transform * Matrix4::from_column_slice(&n)),
+            _ => None,
+        };
+        if let Some(transform) = transform {
+            self.state.transform = transform;
+            return;
+        }
+
+        match directive.name.as_str() {
+            "Identity" | "Translate" | "Scale" | "Rotate" | "LookAt" | "Transform"
+            | "ConcatTransform" => {}
+            "Camera" => match self.state.transform.try_inverse() {
+                Some(camera_to_world) => self.camera = Some((directive.clone(), camera_to_world)),
+                None => self.problems.push(Problem {
+                    location: Location::Line(directive.line, None),
+                    reason: Reason::Invalid(String::from("the camera's transform cant be undone")),
+                }),
+            },
+            "Film" => {
+                if directive.kind() != "image" {
+                    self.unsupported(directive);
+                }
+                let (width, height) = self.film;
+                let size = |name: &str, default: u32| {
+                    directive
+                        .number(name)
+                        .map(|size| size.max(1.0) as u32)
+                        .unwrap_or(default)
+                };
+                self.film = (size("xresolution", width), size("yresolution", height));
+            }
+            "Sampler" => {
+                let kind = match directive.kind() {
+                    "random" => Some(SamplerKind::Independent),
+                    "stratified" => Some(SamplerKind::Stratified),
+                    "halton" => Some(SamplerKind::Halton),
+                    "sobol" => Some(SamplerKind::Sobol),
+                    _ => None,
+                };
+                match kind {
+                    Some(kind) => self.scene.settings.sampler = kind,
+                    None => self.unsupported(directive),
+                }
+                // stratified counts its samples in each direction instead
+                let samples = match (directive.number("xsamples"), directive.number("ysamples")) {
+                    (Some(x), Some(y)) => Some(x * y),
+                    _ => directive.number("pixelsamples"),
+                };
+                if let Some(samples) = samples {
+                    self.scene.settings.samples_per_pixel = samples.max(1.0) as u32;
+                }
+            }
+            "PixelFilter" => {
+                let kind = match directive.kind() {
+                    "box" => Some(FilterKind::Box),
+                    "triangle" => Some(FilterKind::Tent),
+                    "gaussian" => Some(FilterKind::Gaussian),
+                    "mitchell" => Some(FilterKind::Mitchell),
+                    _ => None,
+                };
+                match kind {
+                    Some(kind) => self.scene.settings.filter = kind,
+                    None => self.unsupported(directive),
+                }
+            }
+            "Integrator" => {
+                if !matches!(directive.kind(), "path" | "whitted" | "directlighting") {
+                    let why = format!(
+                        "Integrator {} isnt supported, rays just bounce the usual way",
+                        directive.kind()
+                    );
+                    self.warn(directive, why);
+                }
+                if let Some(depth) = directive.number("maxdepth") {
+                    self.scene.settings.max_depth = Some(depth.clamp(0.0, u8::MAX as f32) as u8);
+                }
+            }
+            // only changes how fast pbrt goes, not what it draws
+            "Accelerator" => {}
+            "WorldBegin" => self.world_begin(),
+            "WorldEnd" => {}
+            "AttributeBegin" => self.attributes.push(self.state.clone()),
+            "AttributeEnd" => match self.attributes.pop() {
+                Some(state) => self.state = state,
+                None => self.warn(
+                    directive,
+                    String::from("AttributeEnd without AttributeBegin"),
+                ),
+            },
+            "TransformBegin" => self.transforms.push(self.state.transform),
+            "TransformEnd" => match self.transforms.pop() {
+                Some(transform) => self.state.transform = transform,
+                None => self.warn(
+                    directive,
+                    String::from("TransformEnd without TransformBegin"),
+                ),
+            },
+            "Material" => {
+                if let Some(material) = self.material(directive, directive.kind()) {
+                    self.state.material = material;
+                }
+            }
+            "MakeNamedMaterial" => {
+                let kind = directive.text("type").unwrap_or("").to_string();
+                if let Some(material) = self.material(directive, &kind) {
+                    let name = directive.kind().to_string();
+                    self.scene.materials.insert(
+                        name.clone(),
+                        Material {
+                            surface: material.clone(),
+                            imported: false,
+                        },
+                    );
+                    self.named_materials.insert(name, material);
+                }
+            }
+            "NamedMaterial" => match self.named_materials.get(directive.kind()) {
+                Some(material) => self.state.material = material.clone(),
+                None => {
+                    let why = format!("there is no material called {}", directive.kind());
+                    self.warn(directive, why);
+                }
+            },
+            "AreaLightSource" => {
+                if directive.kind() == "diffuse" {
+                    self.state.area_light =
+                        Some(self.colour(directive, "L").unwrap_or(Rgba::WHITE));
+                } else {
+                    self.unsupported(directive);
+                }
+            }
+            "LightSource" => self.light(directive),
+            "Shape" => {
+                if !self.in_object {
+                    self.shape(directive);
+                }
+            }
+            "ObjectBegin" => {
+                self.in_object = true;
+                self.warn(
+                    directive,
+                    String::from("object instancing isnt supported, so its shapes were left out"),
+                );
+                self.attributes.push(self.state.clone());
+            }
+            "ObjectEnd" => {
+                self.in_object = false;
+                if let Some(state) = self.attributes.pop() {
+                    self.state = state;
+                }
+            }
+            _ => self.unsupported(directive),
+        }
+    }
+
+    // the numbers written after a directive, if there are as many as it needs
+    fn numbers(
+        &mut self,
+        directive: &Directive,
+        numbers: &[f32],
+        needed: usize,
+    ) -> Option<Vec<f32>> {
+        if numbers.len() != needed {
+            self.problems.push(Problem {
+                location: Location::Line(directive.line, None),
+                reason: Reason::BadVectorLength {
+                    expected: needed,
+                    found: numbers.len(),
+                },
+            });
+            return None;
+        }
+        Some(numbers.to_vec())
+    }
+
+    fn colour(&mut self, directive: &Directive, name: &str) -> Option<Rgba> {
+        let param = directive.param(name)?;
+        if !matches!(param.kind.as_str(), "rgb" | "color") {
+            let why = format!(
+                "{} {} isnt supported, only rgb colours are, so it was left as the default",
+                param.kind, name
+            );
+            self.warn(directive, why);
+            return None;
+        }
+        match directive.numbers(name)?.as_slice() {
+            [r, g, b] => Some(Rgba::from_rgb(*r, *g, *b)),
+            numbers => {
+                self.problems.push(Problem {
+                    location: Location::Line(directive.line, None),
+                    reason: Reason::BadVectorLength {
+                        expected: 3,
+                        found: numbers.len(),
+                    },
+                });
+                None
+            }
+        }
+    }
+
+    fn material(&mut self, directive: &Directive, kind: &str) -> Option<Arc<dyn Surface>> {
+        let grey = |value: f32| Rgba::from_gray(value);
+        match kind {
+            "matte" => {
+                let colour = self.colour(directive, "Kd").unwrap_or(grey(0.5));
+                Some(Arc::new(Diffuse::new(colour, SAMPLES)))
+            }
+            "mirror" => {
+                let colour = self.colour(directive, "Kr").unwrap_or(grey(0.9));
+                Some(Arc::new(Specular::with_colour(colour)))
+            }
+            "glass" => {
+                self.warn(
+                    directive,
+                    String::from("glass doesnt let light through here, so it is a mirror"),
+                );
+                let colour = self.colour(directive, "Kr").unwrap_or(grey(1.0));
+                Some(Arc::new(Specular::with_colour(colour)))
+            }
+            "metal" => {
+                // metals are coloured by their spectra, which we cant read, so they are all silver
+                let roughness = directive
+                    .number("roughness")
+                    .or(directive.number("uroughness"))
+                    .unwrap_or(0.01);
+                Some(Arc::new(MetallicRoughness::new(
+                    grey(0.9),
+                    1.0,
+                    roughness,
+                    SAMPLES,
+                )))
+            }
+            _ => {
+                let why = format!("{} materials arent supported, so it is matte", kind);
+                self.warn(directive, why);
+                let colour = self.colour(directive, "Kd").unwrap_or(grey(0.5));
+                Some(Arc::new(Diffuse::new(colour, SAMPLES)))
+            }
+        }
+    }
+
+    fn light(&mut self, directive: &Directive) {
+        let world = self.mirror * self.state.transform;
+        match directive.kind() {
+            "point" | "spot" => {
+                if directive.kind() == "spot" {
+                    self.warn(
+                        directive,
+                        String::from("spot lights shine every way here, like point lights"),
+                    );
+                }
+                let from = directive.numbers("from").unwrap_or_default();
+                let from = match from.as_slice() {
+                    [x, y, z] => Point3::new(*x, *y, *z),
+                    _ => Point3::origin(),
+                };
+                let position = world.transform_point(&from).coords;
+                self.scene
+                    .add(Arc::new(PointLight::new(position, LIGHT_RADIUS)));
+            }
+            "infinite" => {
+                if directive.param("mapname").is_some() {
+                    self.warn(
+                        directive,
+                        String::from(
+                            "infinite light maps arent supported, just its colour is used",
+                        ),
+                    );
+                }
+                let colour = self.colour(directive, "L").unwrap_or(Rgba::WHITE);
+                self.scene.add(Arc::new(WorldLight::new(colour, colour)));
+            }
+            _ => self.unsupported(directive),
+        }
+    }
+
+    fn shape(&mut self, directive: &Directive) {
+        let world = self.mirror * self.state.transform;
+        let surface: Arc<dyn Surface> = match self.state.area_light {
+            Some(colour) => Arc::new(Emissive::new(colour)),
+            None => self.state.material.clone(),
+        };
+        let mesh = match directive.kind() {
+            "sphere" => {
+                let centre = world.transform_point(&Point3::origin()).coords;
+                // spheres can only be scaled the same every way, so squashed ones come out round
+                let scale = world.fixed_view::<3, 3>(0, 0).determinant().abs().cbrt();
+                let radius = directive.number("radius").unwrap_or(1.0) * scale;
+                self.scene
+                    .add(Arc::new(Sphere::with_shader(centre, radius, surface)));
+                return;
+            }
+            "trianglemesh" => self.triangle_mesh(directive),
+            "plymesh" => {
+                let Some(file) = directive.text("filename") else {
+                    self.problems.push(Problem {
+                        location: Location::Line(directive.line, None),
+                        reason: Reason::Invalid(String::from("plymesh needs a filename")),
+                    });
+                    return;
+                };
+                let file = relative_to(self.file_name, file);
+                match read_ply(&file) {
+                    Ok(mesh) => {
+                        self.scene.sources.push(file);
+                        Some(mesh)
+                    }
+                    Err(error) => {
+                        self.problems.append(&mut error.into_problems());
+                        None
+                    }
+                }
+            }
+            _ => {
+                self.unsupported(directive);
+                None
+            }
+        };
+        let Some(mut mesh) = mesh else {
+            return;
+        };
+        mesh.transform(&world);
+        match mesh.triangles(&surface, "triangle") {
+            Ok(triangles) => {
+                for triangle in triangles {
+                    self.scene.add(Arc::new(triangle));
+                }
+            }
+            Err(mut problems) => self.problems.append(&mut problems),
+        }
+    }
+
+    fn triangle_mesh(&mut self, directive: &Directive) -> Option<Mesh> {
+        let groups = |name: &str, size: usize| {
+            directive.numbers(name).map(|numbers| {
+                numbers
+                    .chunks_exact(size)
+                    .map(|chunk| chunk.to_vec())
+                    .collect::<Vec<Vec<f32>>>()
+            })
+        };
+        let Some(positions) = groups("P", 3) else {
+            self.problems.push(Problem {
+                location: Location::Line(directive.line, None),
+                reason: Reason::Invalid(String::from("trianglemesh needs P")),
+            });
+            return None;
+        };
+        let normals = groups("N", 3);
+        let uvs = groups("uv", 2).or(groups("st", 2));
+        let vertices = positions
+            .iter()
+            .enumerate()
+            .map(|(i, position)| {
+                let mut vertex = Vertex::at(Vector3::new(position[0], position[1], position[2]));
+                vertex.normal = normals
+                    .as_ref()
+                    .and_then(|normals| normals.get(i))
+                    .map(|n| Vector3::new(n[0], n[1], n[2]));
+                vertex.uv = uvs
+                    .as_ref()
+                    .and_then(|uvs| uvs.get(i))
+                    .map(|uv| nalgebra::Vector2::new(uv[0], uv[1]));
+                vertex
+            })
+            .collect::<Vec<Vertex>>();
+        // a single triangle can leave out its indices
+        let faces = match groups("indices", 3) {
+            Some(faces) => {
+                if let Some(index) = faces
+                    .iter()
+                    .flatten()
+                    .find(|&&index| index < 0.0 || index.fract() != 0.0)
+                {
+                    self.problems.push(Problem {
+                        location: Location::Line(directive.line, None),
+                        reason: Reason::Invalid(format!(
+                            "{} in indices isnt a vertex number",
+                            index
+                        )),
+                    });
+                    return None;
+                }
+                faces
+                    .iter()
+                    .map(|face| face.iter().map(|&i| i as usize).collect())
+                    .collect()
+            }
+            None if vertices.len() == 3 => vec![vec![0, 1, 2]],
+            None => {
+                self.problems.push(Problem {
+                    location: Location::Line(directive.line, None),
+                    reason: Reason::Invalid(String::from("trianglemesh needs indices")),
+                });
+                return None;
+            }
+        };
+        Some(Mesh { vertices, faces })
+    }
+
+    // the camera gets built here since everything about it is known by now
+    fn world_begin(&mut self) {
+        self.in_world = true;
+        self.state.transform = Matrix4::identity();
+        let (directive, camera_to_world) = self.camera.clone().unwrap_or((
+            Directive {
+                name: String::from("Camera"),
+                line: 0,
+                args: vec![Arg::Text(String::from("perspective"))],
+                params: vec![],
+            },
+            Matrix4::identity(),
+        ));
+
+        // pbrt cameras look along +z with +y up and +x to the right of the picture
+        let axis = |x: f32, y: f32, z: f32| (camera_to_world * Vector4::new(x, y, z, 0.0)).xyz();
+        let (right, up, forward) = (
+            axis(1.0, 0.0, 0.0),
+            axis(0.0, 1.0, 0.0),
+            axis(0.0, 0.0, 1.0),
+        );
+        if forward.cross(&up).dot(&right) < 0.0 {
+            self.mirror = Matrix4::new_nonuniform_scaling(&Vector3::new(-1.0, 1.0, 1.0));
+        }
+        let to_world = self.mirror * camera_to_world;
+        let position = to_world.transform_point(&Point3::origin()).coords;
+        let forward = (to_world * Vector4::new(0.0, 0.0, 1.0, 0.0)).xyz();
+        let (width, height) = self.film;
+        let mut camera = Camera::look_at(position, position + forward, width, height);
+        camera.up = (to_world * Vector4::new(0.0, 1.0, 0.0, 0.0)).xyz();
+
+        // sizes in pbrt go along the shorter side of the picture
+        let aspect = width as f32 / height as f32;
+        match directive.kind() {
+            "perspective" => {
+                let fov = directive.number("fov").unwrap_or(90.0).to_radians();
+                let vertical = if aspect >= 1.0 {
+                    fov
+                } else {
+                    2.0 * ((fov / 2.0).tan() / aspect).atan()
+                };
+                camera.vertical_fov = vertical.to_degrees();
+            }
+            "orthographic" => {
+                let height = if aspect >= 1.0 { 2.0 } else { 2.0 / aspect };
+                camera.projection = Arc::new(Orthographic::new(height));
+            }
+            "environment" => camera.projection = Arc::new(Equirectangular::new()),
+            _ => {
+                let why = format!(
+                    "{} cameras arent supported, so it is a perspective one",
+                    directive.kind()
+                );
+                self.warn(&directive, why);
+            }
+        }
+        if let Some(radius) = directive.number("lensradius") {
+            camera.aperture_radius = radius;
+            camera.focus_distance = directive.number("focaldistance").unwrap_or(1e6);
+        }
+        self.scene.cameras.push(NamedCamera {
+            name: String::from("camera"),
+            camera,
+        });
+    }
+}
+
+// pbrt's LookAt, which goes from the world to the camera's space
+fn look_at(numbers: &[f32]) -> Matrix4<f32> {
+    let [eye, target, up] =
+        [0, 3, 6].map(|i| Vector3::new(numbers[i], numbers[i + 1], numbers[i + 2]));
+    let forward = (target - eye).normalize();
+    let right = up.normalize().cross(&forward).normalize();
+    let up = forward.cross(&right);
+    let camera_to_world = Matrix4::from_columns(&[
+        right.push(0.0),
+        up.push(0.0),
+        forward.push(0.0),
+        eye.push(1.0),
+    ]);
+    camera_to_world.try_inverse().unwrap_or(Matrix4::identity())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        sampler::SamplerKind,
+        scene::{
+            description::SurfaceDescription,
+            error::{Location, Reason},
+            temp_folder::TempFolder,
+            Scene,
+        },
+    };
+
+    // a mirrored ball and a glowing triangle either side of the middle, and a ply triangle out of
+    // sight below them. pbrt's LookAt has x going left in the picture, so the ball ends up on the
+    // left
+    const SCENE: &str = "# a test scene
+LookAt 0 0 5  0 0 0  0 1 0
+Camera \"perspective\" \"float fov\" [30]
+Film \"image\" \"integer xresolution\" [200] \"integer yresolution\" [100]
+Sampler \"halton\" \"integer pixelsamples\" 16
+WorldBegin
+LightSource \"infinite\" \"rgb L\" [0.1 0.1 0.1]
+AttributeBegin
+  Translate 1 0 0
+  Material \"mirror\"
+  Shape \"sphere\" \"float radius\" 0.5
+AttributeEnd
+AttributeBegin
+  AreaLightSource \"diffuse\" \"rgb L\" [4 4 4]
+  Shape \"trianglemesh\" \"integer indices\" [0 1 2]
+    \"point P\" [-2 -0.5 -1  -1 -0.5 -1  -1.5 0.5 -1]
+AttributeEnd
+Shape \"plymesh\" \"string filename\" \"mesh.ply\"
+Shape \"disk\"
+WorldEnd
+";
+
+    #[test]
+    fn pbrt_scenes_come_across() {
+        let folder = TempFolder::new();
+        folder.write(
+            "mesh.ply",
+            "ply\nformat ascii 1.0\nelement vertex 3\nproperty float x\nproperty float y\n\
+             property float z\nelement face 1\nproperty list uchar int vertex_indices\n\
+             end_header\n0 -3 -1\n1 -3 -1\n0 -4 -1\n3 0 1 2\n",
+        );
+        let file = folder.write("scene.pbrt", SCENE);
+        let scene = Scene::from_pbrt(&file).unwrap();
+
+        let camera = &scene.cameras[0].camera;
+        assert_eq!((camera.width, camera.height), (200, 100));
+        assert!((camera.vertical_fov - 30.0).abs() < 1e-3);
+        assert_eq!(scene.settings.sampler, SamplerKind::Halton);
+        assert_eq!(scene.settings.samples_per_pixel, 16);
+        // the light, the ball, and a triangle each from the mesh and the ply file
+        assert_eq!(scene.objects().len(), 4);
+
+        let surface_at = |x: f32| {
+            let ray = camera.pixel_ray(x, 50.0, 200, 100, (0.5, 0.5)).unwrap();
+            scene.closest_hit(&ray).1.map(|surface| surface.describe())
+        };
+        assert!(matches!(
+            surface_at(63.0),
+            Some(SurfaceDescription::Specular(_))
+        ));
+        match surface_at(147.0) {
+            Some(SurfaceDescription::Emissive(emissive)) => {
+                assert_eq!(emissive.colour, [4.0, 4.0, 4.0])
+            }
+            other => panic!("expected the glowing triangle, got {:?}", other),
+        }
+
+        // the disk got left out, and said so
+        assert_eq!(scene.warnings.len(), 1);
+        assert_eq!(scene.warnings[0].location, Location::Line(19, None));
+    }
+
+    #[test]
+    fn pbrt_problems_have_line_numbers() {
+        let folder = TempFolder::new();
+        let file = folder.write(
+            "broken.pbrt",
+            "WorldBegin\nTranslate 1 2\nShape \"sphere\"\n",
+        );
+        let error = Scene::from_pbrt(&file).unwrap_err();
+        assert_eq!(error.problems.len(), 1);
+        assert_eq!(error.problems[0].location, Location::Line(2, None));
+        assert_eq!(
+            error.problems[0].reason,
+            Reason::BadVectorLength {
+                expected: 3,
+                found: 2
+            }
+        );
+
+        let file = folder.write("unquoted.pbrt", "WorldBegin\n\nShape \"sphere\n");
+        let error = Scene::from_pbrt(&file).unwrap_err();
+        assert_eq!(error.problems[0].location, Location::Line(3, None));
+    }
+
+    #[test]
+    fn bad_indices_and_axes_are_problems() {
+        let folder = TempFolder::new();
+        let file = folder.write(
+            "indices.pbrt",
+            "WorldBegin\nShape \"trianglemesh\" \"point3 P\" [0 0 0 1 0 0 0 1 0]\n  \"integer indices\" [0 -1 2]\n",
+        );
+        let error = Scene::from_pbrt(&file).unwrap_err();
+        assert_eq!(error.problems.len(), 1);
+        assert_eq!(error.problems[0].location, Location::Line(2, None));
+        assert_eq!(
+            error.problems[0].reason,
+            Reason::Invalid(String::from("-1 in indices isnt a vertex number"))
+        );
+
+        let file = folder.write("axis.pbrt", "Rotate 90 0 0 0\nWorldBegin\n");
+        let error = Scene::from_pbrt(&file).unwrap_err();
+        assert_eq!(error.problems.len(), 1);
+        assert_eq!(error.problems[0].location, Location::Line(1, None));
+    }
+}
@@ -0,0 +1,689 @@
+use std::{fs, sync::Arc};
+
+use epaint::Rgba;
+use nalgebra::{Vector2, Vector3};
+
+use crate::{
+    objects::sphere::Sphere,
+    scene::{
+        error::{Location, Problem, Reason, SceneError},
+        mesh::{Mesh, Vertex},
+        Scene, DEPTH,
+    },
+    surfaces::{diffuse::Diffuse, Surface},
+};
+
+// how many rays the mesh's surface sends off when something hits it
+const SAMPLES: usize = 3;
+
+impl Scene {
+    // a .ply mesh, in text or binary of either endianness. faces become triangles (polygons get
+    // fanned out from their first corner) and vertex colours get blended across them. files with
+    // no faces are point clouds, which need point_radius to turn each point into a sphere
+    pub fn from_ply(file_name: &str, point_radius: Option<f32>) -> Result<Scene, SceneError> {
+        let mesh = read_ply(file_name)?;
+        let mut scene = Scene::new(DEPTH);
+        scene.sources.push(file_name.to_string());
+        let surface: Arc<dyn Surface> = Arc::new(Diffuse::new(Rgba::WHITE, SAMPLES));
+        if mesh.faces.is_empty() {
+            let Some(radius) = point_radius else {
+                return Err(SceneError::single(
+                    file_name,
+                    Location::File,
+                    Reason::Invalid(String::from(
+                        "only has points, give them a radius to see them as spheres",
+                    )),
+                ));
+            };
+            for vertex in &mesh.vertices {
+                let surface = match vertex.colour {
+                    Some(colour) => Arc::new(Diffuse::new(colour, SAMPLES)),
+                    None => surface.clone(),
+                };
+                scene.add(Arc::new(Sphere::with_shader(
+                    vertex.position,
+                    radius,
+                    surface,
+                )));
+            }
+            return Ok(scene);
+        }
+
+        let triangles = mesh
+            .triangles(&surface, "face")
+            .map_err(|problems| SceneError::new(file_name, problems))?;
+        for triangle in triangles {
+            scene.add(Arc::new(triangle));
+        }
+        Ok(scene)
+    }
+}
+
+// the vertices and faces in a ply file, for things that want to do more with them than from_ply
+pub fn read_ply(file_name: &str) -> Result<Mesh, SceneError> {
+    let bytes = fs::read(file_name).map_err(|error| {
+        SceneError::single(
+            file_name,
+            Location::File,
+            Reason::Unreadable(error.to_string()),
+        )
+    })?;
+    let problem = |problem| SceneError::new(file_name, vec![problem]);
+    let ply = Ply::parse(&bytes).map_err(problem)?;
+    Ok(Mesh {
+        vertices: ply.vertices().map_err(problem)?,
+        faces: ply
+            .faces()
+            .map_err(|problems| SceneError::new(file_name, problems))?,
+    })
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Kind {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+#[derive(Clone, Debug)]
+struct Property {
+    name: String,
+    kind: Kind,
+    // lists start with how many there are, stored as this kind
+    count: Option<Kind>,
+}
+
+#[derive(Clone, Debug)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+// one property of one row, everything gets read as f64 whatever it was stored as
+#[derive(Clone, Debug)]
+enum Value {
+    One(f64),
+    List(Vec<f64>),
+}
+
+// the file read into rows, before anything is made out of it
+struct Ply {
+    elements: Vec<(Element, Vec<Vec<Value>>)>,
+}
+
+impl Ply {
+    fn parse(bytes: &[u8]) -> Result<Ply, Problem> {
+        let problem = |line: usize, reason| Problem {
+            location: Location::Line(line, None),
+            reason,
+        };
+        // the header is always text, and ends at the end of its end_header line
+        let end = find(bytes, b"end_header")
+            .ok_or_else(|| problem(1, Reason::Syntax(String::from("no end_header"))))?;
+        let body_start = bytes[end..]
+            .iter()
+            .position(|&byte| byte == b'\n')
+            .map(|newline| end + newline + 1)
+            .unwrap_or(bytes.len());
+        let header = std::str::from_utf8(&bytes[..end])
+            .map_err(|_| Problem {
+                location: Location::File,
+                reason: Reason::NotUtf8,
+            })?
+            .lines()
+            .collect::<Vec<&str>>();
+
+        if header.first().map(|line| line.trim()) != Some("ply") {
+            return Err(problem(
+                1,
+                Reason::Syntax(String::from("should start with ply")),
+            ));
+        }
+        let mut format = None;
+        let mut elements: Vec<Element> = vec![];
+        for (number, line) in header.iter().enumerate().skip(1) {
+            let line_number = number + 1;
+            let words = line.split_whitespace().collect::<Vec<&str>>();
+            match words.as_slice() {
+                [] | ["comment", ..] | ["obj_info", ..] => {}
+                ["format", kind, _version] => {
+                    format = Some(match *kind {
+                        "ascii" => Format::Ascii,
+                        "binary_little_endian" => Format::BinaryLittleEndian,
+                        "binary_big_endian" => Format::BinaryBigEndian,
+                        other => {
+                            return Err(problem(
+                                line_number,
+                                Reason::UnknownKind {
+                                    found: other.to_string(),
+                                    expected: vec![
+                                        String::from("ascii"),
+                                        String::from("binary_little_endian"),
+                                        String::from("binary_big_endian"),
+                                    ],
+                                },
+                            ))
+                        }
+                    })
+                }
+                ["element", name, count] => elements.push(Element {
+                    name: name.to_string(),
+                    count: count
+                        .parse()
+                        .map_err(|_| problem(line_number, Reason::BadNumber(count.to_string())))?,
+                    properties: vec![],
+                }),
+                ["property", "list", count, kind, name] => {
+                    let property = Property {
+                        name: name.to_string(),
+                        kind: Kind::from_name(kind)
+                            .map_err(|reason| problem(line_number, reason))?,
+                        count: Some(
+                            Kind::from_name(count)
+                                .map_err(|reason| problem(line_number, reason))?,
+                        ),
+                    };
+                    elements
+                        .last_mut()
+                        .ok_or_else(|| problem(line_number, no_element()))?
+                        .properties
+                        .push(property);
+                }
+                ["property", kind, name] => {
+                    let property = Property {
+                        name: name.to_string(),
+                        kind: Kind::from_name(kind)
+                            .map_err(|reason| problem(line_number, reason))?,
+                        count: None,
+                    };
+                    elements
+                        .last_mut()
+                        .ok_or_else(|| problem(line_number, no_element()))?
+                        .properties
+                        .push(property);
+                }
+                _ => {
+                    return Err(problem(
+                        line_number,
+                        Reason::Syntax(format!("dont know what {:?} means", line.trim())),
+                    ))
+                }
+            }
+        }
+        let format = format.ok_or_else(|| problem(2, Reason::Syntax(String::from("no format"))))?;
+
+        let mut reader = match format {
+            // the body starts on the line after end_header
+            Format::Ascii => {
+                Reader::Ascii(AsciiReader::new(&bytes[body_start..], header.len() + 2))
+            }
+            _ => Reader::Binary {
+                bytes: &bytes[body_start..],
+                at: 0,
+                little_endian: format == Format::BinaryLittleEndian,
+            },
+        };
+        let mut read = vec![];
+        for element in elements {
+            // the count comes straight from the header, so it cant be trusted to allocate with
+            let mut rows = vec![];
+            for _ in 0..element.count {
+                let mut row = Vec::with_capacity(element.properties.len());
+                for property in &element.properties {
+                    row.push(match property.count {
+                        None => Value::One(reader.read(property.kind)?),
+                        Some(count) => {
+                            let count = reader.read(count)? as usize;
+                            let list = (0..count)
+                                .map(|_| reader.read(property.kind))
+                                .collect::<Result<Vec<f64>, Problem>>()?;
+                            Value::List(list)
+                        }
+                    });
+                }
+                rows.push(row);
+            }
+            read.push((element, rows));
+        }
+        Ok(Ply { elements: read })
+    }
+
+    fn element(&self, name: &str) -> Option<&(Element, Vec<Vec<Value>>)> {
+        self.elements
+            .iter()
+            .find(|(element, _)| element.name == name)
+    }
+
+    fn vertices(&self) -> Result<Vec<Vertex>, Problem> {
+        let Some((element, rows)) = self.element("vertex") else {
+            return Ok(vec![]);
+        };
+        let index = |name: &str| {
+            element
+                .properties
+                .iter()
+                .position(|property| property.name == name && property.count.is_none())
+        };
+        let [x, y, z] = ["x", "y", "z"].map(index);
+        let (Some(x), Some(y), Some(z)) = (x, y, z) else {
+            return Err(Problem {
+                location: Location::Path(String::from("vertex")),
+                reason: Reason::Invalid(String::from("needs x, y and z")),
+            });
+        };
+        let normal = match ["nx", "ny", "nz"].map(index) {
+            [Some(x), Some(y), Some(z)] => Some([x, y, z]),
+            _ => None,
+        };
+        let colour = match ["red", "green", "blue"].map(index) {
+            [Some(r), Some(g), Some(b)] => Some([r, g, b]),
+            _ => None,
+        };
+        // texture coordinates go by a few different names
+        let uv = [["u", "v"], ["s", "t"], ["texture_u", "texture_v"]]
+            .into_iter()
+            .find_map(|names| match names.map(index) {
+                [Some(u), Some(v)] => Some([u, v]),
+                _ => None,
+            });
+        // 8 bit colours are srgb like any picture, ones stored as numbers from 0 to 1 already
+        // arent
+        let bytes = colour.is_some_and(|[r, _, _]| element.properties[r].kind == Kind::U8);
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let number = |i: usize| match &row[i] {
+                    Value::One(value) => *value as f32,
+                    Value::List(_) => 0.0,
+                };
+                Vertex {
+                    position: Vector3::new(number(x), number(y), number(z)),
+                    normal: normal.map(|[x, y, z]| Vector3::new(number(x), number(y), number(z))),
+                    uv: uv.map(|[u, v]| Vector2::new(number(u), number(v))),
+                    colour: colour.map(|[r, g, b]| {
+                        if bytes {
+                            let [r, g, b] = [r, g, b].map(|i| number(i) as u8);
+                            Rgba::from_srgba_unmultiplied(r, g, b, 255)
+                        } else {
+                            Rgba::from_rgb(number(r), number(g), number(b))
+                        }
+                    }),
+                }
+            })
+            .collect())
+    }
+
+    // the corners of each face, which have to be whole numbers that arent negative
+    fn faces(&self) -> Result<Vec<Vec<usize>>, Vec<Problem>> {
+        let Some((element, rows)) = self.element("face") else {
+            return Ok(vec![]);
+        };
+        let Some(index) = element.properties.iter().position(|property| {
+            property.count.is_some()
+                && (property.name == "vertex_indices" || property.name == "vertex_index")
+        }) else {
+            return Ok(vec![]);
+        };
+        let mut faces = vec![];
+        let mut problems = vec![];
+        for (number, row) in rows.iter().enumerate() {
+            let Value::List(corners) = &row[index] else {
+                faces.push(vec![]);
+                continue;
+            };
+            match corners
+                .iter()
+                .find(|&&corner| corner < 0.0 || corner.fract() != 0.0)
+            {
+                Some(corner) => problems.push(Problem {
+                    location: Location::Path(format!("face {}", number)),
+                    reason: Reason::Invalid(format!("{} isnt a vertex number", corner)),
+                }),
+                None => faces.push(corners.iter().map(|&corner| corner as usize).collect()),
+            }
+        }
+        if !problems.is_empty() {
+            return Err(problems);
+        }
+        Ok(faces)
+    }
+}
+
+impl Kind {
+    fn from_name(name: &str) -> Result<Kind, Reason> {
+        match name {
+            "char" | "int8" => Ok(Kind::I8),
+            "uchar" | "uint8" => Ok(Kind::U8),
+            "short" | "int16" => Ok(Kind::I16),
+            "ushort" | "uint16" => Ok(Kind::U16),
+            "int" | "int32" => Ok(Kind::I32),
+            "uint" | "uint32" => Ok(Kind::U32),
+            "float" | "float32" => Ok(Kind::F32),
+            "double" | "float64" => Ok(Kind::F64),
+            _ => Err(Reason::UnknownKind {
+                found: name.to_string(),
+                expected: vec![
+                    "char", "uchar", "short", "ushort", "int", "uint", "float", "double",
+                ]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            }),
+        }
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Kind::I8 | Kind::U8 => 1,
+            Kind::I16 | Kind::U16 => 2,
+            Kind::I32 | Kind::U32 | Kind::F32 => 4,
+            Kind::F64 => 8,
+        }
+    }
+}
+
+enum Reader<'a> {
+    Ascii(AsciiReader<'a>),
+    Binary {
+        bytes: &'a [u8],
+        at: usize,
+        little_endian: bool,
+    },
+}
+
+impl Reader<'_> {
+    fn read(&mut self, kind: Kind) -> Result<f64, Problem> {
+        match self {
+            Reader::Ascii(reader) => reader.read(),
+            Reader::Binary {
+                bytes,
+                at,
+                little_endian,
+            } => {
+                let Some(value) = bytes.get(*at..*at + kind.size()) else {
+                    return Err(Problem {
+                        location: Location::File,
+                        reason: Reason::Invalid(String::from(
+                            "ends before everything the header says is there",
+                        )),
+                    });
+                };
+                *at += kind.size();
+                // flip big endian numbers round so everything can be read as little endian
+                let mut value = value.to_vec();
+                if !*little_endian {
+                    value.reverse();
+                }
+                let array = |value: &[u8]| -> [u8; 8] {
+                    let mut array = [0; 8];
+                    array[..value.len()].copy_from_slice(value);
+                    array
+                };
+                let value = array(&value);
+                Ok(match kind {
+                    Kind::I8 => value[0] as i8 as f64,
+                    Kind::U8 => value[0] as f64,
+                    Kind::I16 => i16::from_le_bytes([value[0], value[1]]) as f64,
+                    Kind::U16 => u16::from_le_bytes([value[0], value[1]]) as f64,
+                    Kind::I32 => {
+                        i32::from_le_bytes([value[0], value[1], value[2], value[3]]) as f64
+                    }
+                    Kind::U32 => {
+                        u32::from_le_bytes([value[0], value[1], value[2], value[3]]) as f64
+                    }
+                    Kind::F32 => {
+                        f32::from_le_bytes([value[0], value[1], value[2], value[3]]) as f64
+                    }
+                    Kind::F64 => f64::from_le_bytes(value),
+                })
+            }
+        }
+    }
+}
+
+// numbers separated by any whitespace, keeping track of the line for problems
+struct AsciiReader<'a> {
+    lines: std::iter::Enumerate<std::str::Lines<'a>>,
+    words: std::str::SplitWhitespace<'a>,
+    first_line: usize,
+    line: usize,
+}
+
+impl<'a> AsciiReader<'a> {
+    fn new(bytes: &'a [u8], first_line: usize) -> AsciiReader<'a> {
+        // anything that isnt utf-8 wont be a number either, so cut it off there
+        let text = match std::str::from_utf8(bytes) {
+            Ok(text) => text,
+            Err(error) => std::str::from_utf8(&bytes[..error.valid_up_to()]).unwrap(),
+        };
+        AsciiReader {
+            lines: text.lines().enumerate(),
+            words: "".split_whitespace(),
+            first_line,
+            line: first_line,
+        }
+    }
+
+    fn read(&mut self) -> Result<f64, Problem> {
+        loop {
+            if let Some(word) = self.words.next() {
+                return word.parse().map_err(|_| Problem {
+                    location: Location::Line(self.line, None),
+                    reason: Reason::BadNumber(word.to_string()),
+                });
+            }
+            let Some((number, line)) = self.lines.next() else {
+                return Err(Problem {
+                    location: Location::File,
+                    reason: Reason::Invalid(String::from(
+                        "ends before everything the header says is there",
+                    )),
+                });
+            };
+            self.line = self.first_line + number;
+            self.words = line.split_whitespace();
+        }
+    }
+}
+
+fn find(bytes: &[u8], needle: &[u8]) -> Option<usize> {
+    bytes
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+fn no_element() -> Reason {
+    Reason::Syntax(String::from("property before any element"))
+}
+
+#[cfg(test)]
+mod test {
+    use epaint::Rgba;
+    use nalgebra::Vector3;
+
+    use crate::{
+        renderer::Ray,
+        scene::{
+            description::SurfaceDescription,
+            error::{Location, Reason},
+            temp_folder::TempFolder,
+            Scene,
+        },
+    };
+
+    const HEADER: &str = "ply
+format {} 1.0
+comment a square, red along one side and blue along the other
+element vertex 4
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+element face 1
+property list uchar int vertex_indices
+end_header
+";
+
+    // the square's corners, then one face using all four of them
+    const CORNERS: [([f32; 3], [u8; 3]); 4] = [
+        ([0.0, 1.0, 0.0], [255, 0, 0]),
+        ([2.0, 1.0, 0.0], [0, 0, 255]),
+        ([2.0, 1.0, 2.0], [0, 0, 255]),
+        ([0.0, 1.0, 2.0], [255, 0, 0]),
+    ];
+
+    // the header with the rows after it
+    fn square(format: &str, body: &[u8]) -> Vec<u8> {
+        let mut contents = HEADER.replace("{}", format).into_bytes();
+        contents.extend_from_slice(body);
+        contents
+    }
+
+    fn binary(big_endian: bool) -> Vec<u8> {
+        let mut body = vec![];
+        for (position, colour) in CORNERS {
+            for number in position {
+                body.extend(match big_endian {
+                    true => number.to_be_bytes(),
+                    false => number.to_le_bytes(),
+                });
+            }
+            body.extend(colour);
+        }
+        body.push(4);
+        for corner in [0, 1, 2, 3_i32] {
+            body.extend(match big_endian {
+                true => corner.to_be_bytes(),
+                false => corner.to_le_bytes(),
+            });
+        }
+        body
+    }
+
+    // the colour of the surface where a ray going along y hits the square
+    fn colour_at(scene: &Scene, x: f32, z: f32) -> [f32; 3] {
+        let ray = Ray::new(Vector3::new(x, 0.0, z), Vector3::new(0.0, 1.0, 0.0));
+        match scene.closest_hit(&ray).1.unwrap().describe() {
+            SurfaceDescription::Diffuse(diffuse) => diffuse.colour,
+            other => panic!("expected a diffuse surface, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn every_ply_format_reads_the_same() {
+        let mut ascii = String::new();
+        for (position, colour) in CORNERS {
+            ascii += &format!(
+                "{} {} {} {} {} {}\n",
+                position[0], position[1], position[2], colour[0], colour[1], colour[2]
+            );
+        }
+        ascii += "4 0 1 2 3\n";
+        let folder = TempFolder::new();
+        let files = [
+            folder.write("ascii.ply", square("ascii", ascii.as_bytes())),
+            folder.write("little.ply", square("binary_little_endian", &binary(false))),
+            folder.write("big.ply", square("binary_big_endian", &binary(true))),
+        ];
+        for file in files {
+            let scene = Scene::from_ply(&file, None).unwrap();
+            // the square gets split into two triangles
+            assert_eq!(scene.objects().len(), 2);
+            // and the colours blend from red on the left to blue on the right
+            let red = colour_at(&scene, 0.01, 1.0);
+            let middle = colour_at(&scene, 1.0, 1.5);
+            let blue = colour_at(&scene, 1.99, 1.0);
+            assert!(red[0] > 0.99 && red[2] < 0.01, "{:?}", red);
+            assert!(blue[0] < 0.01 && blue[2] > 0.99, "{:?}", blue);
+            assert!((middle[0] - 0.5).abs() < 0.01 && (middle[2] - 0.5).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn point_clouds_need_a_radius() {
+        let folder = TempFolder::new();
+        let file = folder.write(
+            "points.ply",
+            "ply\nformat ascii 1.0\nelement vertex 2\nproperty float x\nproperty float y\n\
+             property float z\nend_header\n0 5 0\n3 5 0\n",
+        );
+        assert!(Scene::from_ply(&file, None).is_err());
+
+        let scene = Scene::from_ply(&file, Some(0.5)).unwrap();
+        assert_eq!(scene.objects().len(), 2);
+        let ray = Ray::new(Vector3::new(3.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0));
+        let hit = scene.closest_hit(&ray);
+        assert!((hit.0.distance.unwrap() - 4.5).abs() < 1e-3);
+        assert_eq!(hit.1.unwrap().get_value(Rgba::BLACK), Rgba::WHITE);
+    }
+
+    #[test]
+    fn bad_counts_and_corners_are_problems() {
+        let folder = TempFolder::new();
+        // far more vertices than there are bytes for
+        let file = folder.write(
+            "huge.ply",
+            "ply\nformat binary_little_endian 1.0\nelement vertex 99999999999\n\
+             property float x\nproperty float y\nproperty float z\nend_header\n",
+        );
+        assert!(Scene::from_ply(&file, None).is_err());
+
+        let file = folder.write(
+            "corners.ply",
+            "ply\nformat ascii 1.0\nelement vertex 3\nproperty float x\nproperty float y\n\
+             property float z\nelement face 3\nproperty list uchar int vertex_indices\n\
+             end_header\n0 1 0\n2 1 0\n2 1 2\n3 0 1 2\n3 0 -1 2\n3 0 1.5 2\n",
+        );
+        let error = Scene::from_ply(&file, None).unwrap_err();
+        let found = error
+            .problems
+            .iter()
+            .map(|problem| (problem.location.clone(), problem.reason.clone()))
+            .collect::<Vec<(Location, Reason)>>();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    Location::Path(String::from("face 1")),
+                    Reason::Invalid(String::from("-1 isnt a vertex number"))
+                ),
+                (
+                    Location::Path(String::from("face 2")),
+                    Reason::Invalid(String::from("1.5 isnt a vertex number"))
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn ply_problems_have_line_numbers() {
+        let folder = TempFolder::new();
+        let file = folder.write(
+            "broken.ply",
+            square("ascii", b"0 1 0 255 0 0\n2 1 zero 0 0 255\n"),
+        );
+        let error = Scene::from_ply(&file, None).unwrap_err();
+        assert_eq!(error.problems.len(), 1);
+        assert_eq!(error.problems[0].location, Location::Line(15, None));
+        assert_eq!(
+            error.problems[0].reason,
+            Reason::BadNumber(String::from("zero"))
+        );
+    }
+}
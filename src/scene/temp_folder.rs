@@ -0,0 +1,47 @@
+use std::{
+    fs,
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+// how many folders this run has made, so tests running at the same time never share one
+static MADE: AtomicUsize = AtomicUsize::new(0);
+
+// a folder of its own in the system's temp folder for a test to write scene files into, which
+// goes away again when the test is done with it
+pub struct TempFolder {
+    path: PathBuf,
+}
+
+impl TempFolder {
+    pub fn new() -> TempFolder {
+        let path = std::env::temp_dir().join(format!(
+            "cgraphics_{}_{}",
+            std::process::id(),
+            MADE.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&path).unwrap();
+        TempFolder { path }
+    }
+
+    // where a file with this name goes, it can have folders in front of it
+    pub fn path(&self, name: &str) -> String {
+        self.path.join(name).to_str().unwrap().to_string()
+    }
+
+    // writes the file, making any folders it needs, and gives back where it went
+    pub fn write(&self, name: &str, contents: impl AsRef<[u8]>) -> String {
+        let path = self.path(name);
+        if let Some(parent) = self.path.join(name).parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(&path, contents).unwrap();
+        path
+    }
+}
+
+impl Drop for TempFolder {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
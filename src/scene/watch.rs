@@ -0,0 +1,121 @@
+use std::{fs, time::SystemTime};
+
+// keeps an eye on a scene's files by checking when they were last changed, which is all the
+// viewer needs and doesnt need anything running in the background
+#[derive(Clone, Debug, Default)]
+pub struct Watcher {
+    // each file and when it was last changed, None if it couldnt be read
+    files: Vec<(String, Option<SystemTime>)>,
+}
+
+impl Watcher {
+    pub fn new(files: &[String]) -> Watcher {
+        let mut watcher = Watcher::default();
+        for file in files {
+            watcher.watch(file);
+        }
+        watcher
+    }
+
+    // starts watching another file, if it isnt already
+    pub fn watch(&mut self, file: &str) {
+        if self.files.iter().all(|(watched, _)| watched != file) {
+            self.files.push((file.to_string(), modified(file)));
+        }
+    }
+
+    // takes the file's current time as already seen, for changes made by whoever is watching.
+    // the other files keep theirs so changes to them still get noticed
+    pub fn touch(&mut self, file: &str) {
+        match self.files.iter_mut().find(|(watched, _)| watched == file) {
+            Some((_, last)) => *last = modified(file),
+            None => self.watch(file),
+        }
+    }
+
+    // whether any of the files changed (or appeared or went away) since the last time this was
+    // asked
+    pub fn changed(&mut self) -> bool {
+        let mut changed = false;
+        for (file, last) in &mut self.files {
+            let now = modified(file);
+            if now != *last {
+                *last = now;
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = &str> {
+        self.files.iter().map(|(file, _)| file.as_str())
+    }
+}
+
+fn modified(file: &str) -> Option<SystemTime> {
+    fs::metadata(file)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        fs::File,
+        time::{Duration, SystemTime},
+    };
+
+    use serde_json::json;
+
+    use crate::scene::{temp_folder::TempFolder, watch::Watcher, Scene};
+
+    #[test]
+    fn watchers_see_included_files_change() {
+        let folder = TempFolder::new();
+        let piece = folder.write(
+            "piece.json",
+            json!({ "items": [{ "kind": "sphere", "origin": [0.0, 0.0, 0.0], "radius": 1.0 }] })
+                .to_string(),
+        );
+        folder.write("library.json", json!({ "materials": {} }).to_string());
+        let scene = folder.write(
+            "scene.json",
+            json!({ "import_materials": ["library.json"], "includes": [{ "file": "piece.json" }] })
+                .to_string(),
+        );
+
+        let loaded = Scene::from_file(&scene).unwrap();
+        let mut watcher = Watcher::new(&loaded.sources);
+        let names = watcher
+            .files()
+            .map(|file| file.rsplit(['/', '\\']).next().unwrap().to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(names, ["scene.json", "library.json", "piece.json"]);
+        assert!(!watcher.changed());
+
+        // file systems can be slow to notice, so move the time on by hand
+        let later = SystemTime::now() + Duration::from_secs(10);
+        File::options()
+            .write(true)
+            .open(&piece)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert!(watcher.changed());
+        assert!(!watcher.changed());
+
+        // saving the scene from the viewer doesnt count, but the piece changing at the same time
+        // still does
+        for (file, seconds) in [(&scene, 20), (&piece, 30)] {
+            File::options()
+                .write(true)
+                .open(file)
+                .unwrap()
+                .set_modified(SystemTime::now() + Duration::from_secs(seconds))
+                .unwrap();
+        }
+        watcher.touch(&scene);
+        assert!(watcher.changed());
+        assert!(!watcher.changed());
+    }
+}
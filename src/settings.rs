@@ -0,0 +1,76 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{filter::FilterKind, sampler::SamplerKind};
+
+// everything about how a picture gets rendered that isnt the scene or the camera
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RenderSettings {
+    pub sampler: SamplerKind,
+    // with adaptive sampling on this is how many every pixel starts with, and how many more get
+    // added each time a pixel is still too noisy
+    pub samples_per_pixel: u32,
+    pub seed: u64,
+    pub filter: FilterKind,
+    pub adaptive: Option<AdaptiveSettings>,
+    // how many times a ray can bounce, None leaves it up to the scene
+    pub max_depth: Option<u8>,
+    pub integrator: IntegratorKind,
+}
+
+// what gets worked out for each ray that leaves the camera
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegratorKind {
+    // bounces around the scene letting each surface decide what to do
+    Recursive,
+    // just the direction of the surface that got hit, handy for checking geometry
+    Normals,
+}
+
+impl IntegratorKind {
+    pub const ALL: [IntegratorKind; 2] = [IntegratorKind::Recursive, IntegratorKind::Normals];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            IntegratorKind::Recursive => "recursive",
+            IntegratorKind::Normals => "normals",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<IntegratorKind> {
+        IntegratorKind::ALL
+            .into_iter()
+            .find(|kind| kind.name() == name.to_lowercase())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AdaptiveSettings {
+    // relative standard error of a pixel's brightness that counts as clean enough
+    pub noise_threshold: f32,
+    pub max_samples_per_pixel: u32,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        RenderSettings {
+            sampler: SamplerKind::Independent,
+            samples_per_pixel: 1,
+            seed: 0,
+            filter: FilterKind::Box,
+            adaptive: None,
+            max_depth: None,
+            integrator: IntegratorKind::Recursive,
+        }
+    }
+}
+
+impl Default for AdaptiveSettings {
+    fn default() -> Self {
+        AdaptiveSettings {
+            noise_threshold: 0.05,
+            max_samples_per_pixel: 64,
+        }
+    }
+}
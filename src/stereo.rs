@@ -0,0 +1,144 @@
+use epaint::Rgba;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::camera::Camera;
+
+// two cameras a little way apart, one for each eye
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stereo {
+    // how far apart the eyes are, in world units
+    pub interpupillary_distance: f32,
+    // how far in front of the camera the two eyes agree on where things are, anything closer pops
+    // out of the screen
+    pub convergence_distance: f32,
+    pub layout: StereoLayout,
+}
+
+// how both eyes get packed into one picture
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StereoLayout {
+    // left eye on the left
+    SideBySide,
+    // left eye on top
+    OverUnder,
+}
+
+impl Default for Stereo {
+    fn default() -> Self {
+        Stereo {
+            interpupillary_distance: 0.064,
+            convergence_distance: 2.0,
+            layout: StereoLayout::SideBySide,
+        }
+    }
+}
+
+impl Stereo {
+    // side is -1 for the left eye and 1 for the right
+    pub fn eye(&self, camera: &Camera, side: f32) -> Camera {
+        let offset = side * self.interpupillary_distance / 2.0;
+        let mut eye = camera.clone();
+        eye.stereo = None;
+        match camera.projection.for_eye(offset) {
+            // some projections have to move the eye differently for every direction
+            Some(projection) => eye.projection = projection,
+            None => {
+                let (_, right, _) = camera.basis();
+                eye.position += right * offset;
+                eye.target += right * offset;
+                // keep both eyes looking the same way and slant the view in instead, so the
+                // convergence plane lines up without tilting the eyes towards each other
+                eye.frustum_shift.0 -= offset / self.convergence_distance;
+            }
+        }
+        eye
+    }
+
+    // packs the left and right pictures into one
+    pub fn combine(&self, left: Vec<Vec<Rgba>>, right: Vec<Vec<Rgba>>) -> Vec<Vec<Rgba>> {
+        match self.layout {
+            StereoLayout::SideBySide => left
+                .into_iter()
+                .zip(right)
+                .map(|(mut left_row, right_row)| {
+                    left_row.extend(right_row);
+                    left_row
+                })
+                .collect(),
+            StereoLayout::OverUnder => left.into_iter().chain(right).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use epaint::Rgba;
+    use nalgebra::Vector3;
+
+    use crate::{
+        camera::Camera,
+        projection,
+        stereo::{Stereo, StereoLayout},
+    };
+
+    #[test]
+    fn eyes_meet_at_the_convergence_distance() {
+        let mut camera = Camera::look_at(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.0, 0.0),
+            10,
+            10,
+        );
+        let stereo = Stereo {
+            interpupillary_distance: 0.2,
+            convergence_distance: 3.0,
+            layout: StereoLayout::SideBySide,
+        };
+        camera.stereo = Some(stereo);
+        let [left, right] = [-1.0, 1.0].map(|side| {
+            let ray = stereo
+                .eye(&camera, side)
+                .pixel_ray(5.0, 5.0, 10, 10, (0.5, 0.5))
+                .unwrap();
+            ray.at_point((3.0 - ray.origin.x) / ray.direction.x)
+        });
+        assert!((left - Vector3::new(3.0, 0.0, 0.0)).norm() < 1e-5);
+        assert!((right - Vector3::new(3.0, 0.0, 0.0)).norm() < 1e-5);
+
+        // the same goes for the projections that dont have a frustum of their own
+        for name in ["orthographic", "fisheye"] {
+            camera.projection = projection::from_name(name).unwrap();
+            let [left, right] = [-1.0, 1.0].map(|side| {
+                let ray = stereo
+                    .eye(&camera, side)
+                    .pixel_ray(5.0, 5.0, 10, 10, (0.5, 0.5))
+                    .unwrap();
+                ray.at_point((3.0 - ray.origin.x) / ray.direction.x)
+            });
+            assert!((left - right).norm() < 1e-5, "{} eyes dont meet", name);
+        }
+
+        // panoramas keep the eyes the same distance apart in every direction
+        camera.projection = projection::from_name("equirectangular").unwrap();
+        for x in [0.0, 3.0, 7.5] {
+            let [left, right] = [-1.0, 1.0].map(|side| {
+                stereo
+                    .eye(&camera, side)
+                    .pixel_ray(x, 5.0, 10, 10, (0.5, 0.5))
+                    .unwrap()
+            });
+            assert!(((left.origin - right.origin).norm() - 0.2).abs() < 1e-5);
+            assert!((left.direction - right.direction).norm() < 1e-5);
+        }
+
+        let picture = |colour| vec![vec![colour; 4]; 3];
+        let side_by_side = stereo.combine(picture(Rgba::RED), picture(Rgba::BLUE));
+        assert_eq!((side_by_side[0].len(), side_by_side.len()), (8, 3));
+        assert_eq!(
+            (side_by_side[2][3], side_by_side[2][4]),
+            (Rgba::RED, Rgba::BLUE)
+        );
+    }
+}
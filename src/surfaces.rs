@@ -1,14 +1,38 @@
-use std::fmt::Debug;
+use std::{fmt::Debug, sync::Arc};
 
 use epaint::Rgba;
+use nalgebra::Vector2;
 
-use crate::{intersect::TestIntersectionResult, renderer::Ray};
+use crate::{
+    intersect::TestIntersectionResult, renderer::Ray, sampler::Sampler,
+    scene::description::SurfaceDescription,
+};
 
 pub mod diffuse;
+pub mod emissive;
+pub mod metallic_roughness;
 pub mod specular;
+pub mod texture;
 
 pub trait Surface: Send + Sync + Debug {
     fn get_value(&self, other: Rgba) -> Rgba;
-    fn request_rays(&self, normal_ray: &Ray, incoming_ray: &Ray) -> Vec<Ray>;
+    fn request_rays(
+        &self,
+        normal_ray: &Ray,
+        incoming_ray: &Ray,
+        sampler: &mut dyn Sampler,
+    ) -> Vec<Ray>;
     fn intersections_to_colour(&self, rays: Vec<TestIntersectionResult>) -> Rgba;
+    // for saving the scene
+    fn describe(&self) -> SurfaceDescription;
+    // surfaces with textures give back a plain one coloured in for that spot, anything else is
+    // the same everywhere
+    fn at_uv(&self, _uv: Vector2<f32>) -> Option<Arc<dyn Surface>> {
+        None
+    }
+    // the same surface with its colour multiplied by another one, for meshes coloured in at
+    // each corner
+    fn tinted(&self, _colour: Rgba) -> Option<Arc<dyn Surface>> {
+        None
+    }
 }
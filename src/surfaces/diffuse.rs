@@ -1,10 +1,15 @@
-use std::f32;
+use std::{f32, sync::Arc};
 
 use epaint::Rgba;
 use nalgebra::Vector3;
-use rand::random_range;
 
-use crate::{common_maths::maths, renderer::Ray, surfaces::Surface};
+use crate::{
+    common_maths::maths,
+    renderer::Ray,
+    sampler::Sampler,
+    scene::description::{colour_array, DiffuseDescription, SurfaceDescription},
+    surfaces::Surface,
+};
 
 #[allow(dead_code)]
 #[derive(Copy, Clone, Debug)]
@@ -17,14 +22,18 @@ impl Surface for Diffuse {
         return self.colour;
     }
 
-    fn request_rays(&self, normal_ray: &Ray, incoming_ray: &Ray) -> Vec<Ray> {
+    fn request_rays(
+        &self,
+        normal_ray: &Ray,
+        incoming_ray: &Ray,
+        sampler: &mut dyn Sampler,
+    ) -> Vec<Ray> {
         let reflected = maths::reflected_ray(normal_ray, incoming_ray);
         (0..self.samples)
-            .into_iter()
             .map(|_| {
                 Ray::new(
                     normal_ray.origin,
-                    Diffuse::rotate_vector(&reflected.direction),
+                    Diffuse::rotate_vector(&reflected.direction, sampler),
                 )
             })
             .collect::<Vec<Ray>>()
@@ -39,15 +48,27 @@ impl Surface for Diffuse {
         avg = avg + self.colour.multiply(0.5);
         avg
     }
+
+    fn describe(&self) -> SurfaceDescription {
+        SurfaceDescription::Diffuse(DiffuseDescription {
+            colour: colour_array(self.colour),
+            samples: self.samples,
+        })
+    }
+
+    fn tinted(&self, colour: Rgba) -> Option<Arc<dyn Surface>> {
+        Some(Arc::new(Diffuse::new(self.colour * colour, self.samples)))
+    }
 }
 
 impl Diffuse {
-    fn rotate_vector(input: &Vector3<f32>) -> Vector3<f32> {
+    fn rotate_vector(input: &Vector3<f32>, sampler: &mut dyn Sampler) -> Vector3<f32> {
         let length = input.norm();
         let kernel = |x: f32| (1.0 + (f32::consts::PI * x).cos()) / 2.0;
-        let scale_x: f32 = kernel(random_range(0.0..1.0_f32));
-        let scale_y: f32 = kernel(random_range(0.0..1.0_f32));
-        let scale_z: f32 = kernel(random_range(0.0..1.0_f32));
+        let (u, v) = sampler.get_2d();
+        let scale_x: f32 = kernel(u);
+        let scale_y: f32 = kernel(v);
+        let scale_z: f32 = kernel(sampler.get_1d());
         let mut return_val = Vector3::new(input.x * scale_x, input.y * scale_y, input.z * scale_z);
         return_val.scale_mut(return_val.norm() / length);
         return_val
@@ -0,0 +1,47 @@
+use std::sync::Arc;
+
+use epaint::Rgba;
+
+use crate::{
+    intersect::TestIntersectionResult,
+    renderer::Ray,
+    sampler::Sampler,
+    scene::description::{colour_array, EmissiveDescription, SurfaceDescription},
+    surfaces::Surface,
+};
+
+// gives off its own light and doesnt bounce anything, for turning shapes into lamps
+#[derive(Clone, Copy, Debug)]
+pub struct Emissive {
+    pub colour: Rgba,
+}
+
+impl Emissive {
+    pub fn new(colour: Rgba) -> Emissive {
+        Emissive { colour }
+    }
+}
+
+impl Surface for Emissive {
+    fn get_value(&self, _: Rgba) -> Rgba {
+        return self.colour;
+    }
+
+    fn request_rays(&self, _: &Ray, _: &Ray, _: &mut dyn Sampler) -> Vec<Ray> {
+        vec![]
+    }
+
+    fn intersections_to_colour(&self, _: Vec<TestIntersectionResult>) -> Rgba {
+        self.colour
+    }
+
+    fn describe(&self) -> SurfaceDescription {
+        SurfaceDescription::Emissive(EmissiveDescription {
+            colour: colour_array(self.colour),
+        })
+    }
+
+    fn tinted(&self, colour: Rgba) -> Option<Arc<dyn Surface>> {
+        Some(Arc::new(Emissive::new(self.colour * colour)))
+    }
+}
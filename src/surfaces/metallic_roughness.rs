@@ -0,0 +1,115 @@
+use std::sync::Arc;
+
+use epaint::Rgba;
+use nalgebra::{Vector2, Vector3};
+
+use crate::{
+    common_maths::maths,
+    intersect::TestIntersectionResult,
+    renderer::Ray,
+    sampler::Sampler,
+    scene::description::{colour_array, MetallicRoughnessDescription, SurfaceDescription},
+    surfaces::{texture::Texture, Surface},
+};
+
+// the material model gltf files use. metallic goes from paint to bare metal, which tints its
+// reflections its own colour, and roughness from a mirror to a blurry reflection
+#[derive(Clone, Debug)]
+pub struct MetallicRoughness {
+    pub colour: Rgba,
+    pub metallic: f32,
+    pub roughness: f32,
+    // how many rays get sent off each time something rough hits it
+    pub samples: usize,
+    // multiplies the colour
+    pub colour_texture: Option<Arc<Texture>>,
+    // roughness in green and metallic in blue, multiplying the numbers above
+    pub metallic_roughness_texture: Option<Arc<Texture>>,
+}
+
+impl MetallicRoughness {
+    pub fn new(colour: Rgba, metallic: f32, roughness: f32, samples: usize) -> MetallicRoughness {
+        MetallicRoughness {
+            colour,
+            metallic,
+            roughness,
+            samples,
+            colour_texture: None,
+            metallic_roughness_texture: None,
+        }
+    }
+}
+
+impl Surface for MetallicRoughness {
+    fn get_value(&self, _: Rgba) -> Rgba {
+        return self.colour;
+    }
+
+    fn request_rays(
+        &self,
+        normal_ray: &Ray,
+        incoming_ray: &Ray,
+        sampler: &mut dyn Sampler,
+    ) -> Vec<Ray> {
+        let reflected = maths::reflected_ray(normal_ray, incoming_ray);
+        if self.roughness <= 0.0 || self.samples == 0 {
+            return vec![reflected];
+        }
+        // rougher surfaces scatter the reflection further from the mirror direction
+        let direction = reflected.direction.normalize();
+        (0..self.samples)
+            .map(|_| {
+                let (u, v) = sampler.get_2d();
+                let z = 2.0 * u - 1.0;
+                let r = (1.0 - z * z).max(0.0).sqrt();
+                let phi = 2.0 * std::f32::consts::PI * v;
+                let scatter = Vector3::new(r * phi.cos(), r * phi.sin(), z);
+                Ray::new(reflected.origin, direction + scatter * self.roughness)
+            })
+            .collect()
+    }
+
+    fn intersections_to_colour(&self, rays: Vec<TestIntersectionResult>) -> Rgba {
+        let count = rays.len().max(1) as f32;
+        let reflected = rays.into_iter().fold(Rgba::BLACK, |sum, ray| {
+            sum + ray.0.colour.multiply(1.0 / count)
+        });
+        // metal reflects in its own colour, paint reflects whatever is there
+        let tint = Rgba::WHITE.multiply(1.0 - self.metallic) + self.colour.multiply(self.metallic);
+        reflected.multiply(0.5) * tint + self.colour.multiply(0.5)
+    }
+
+    fn describe(&self) -> SurfaceDescription {
+        // textures come from gltf files and dont get written out, just the numbers they multiply
+        SurfaceDescription::MetallicRoughness(MetallicRoughnessDescription {
+            colour: colour_array(self.colour),
+            metallic: self.metallic,
+            roughness: self.roughness,
+            samples: self.samples,
+        })
+    }
+
+    fn at_uv(&self, uv: Vector2<f32>) -> Option<Arc<dyn Surface>> {
+        if self.colour_texture.is_none() && self.metallic_roughness_texture.is_none() {
+            return None;
+        }
+        let mut plain =
+            MetallicRoughness::new(self.colour, self.metallic, self.roughness, self.samples);
+        if let Some(texture) = &self.colour_texture {
+            plain.colour = plain.colour * texture.sample(uv);
+        }
+        if let Some(texture) = &self.metallic_roughness_texture {
+            let numbers = texture.sample(uv);
+            plain.roughness *= numbers.g();
+            plain.metallic *= numbers.b();
+        }
+        Some(Arc::new(plain))
+    }
+
+    fn tinted(&self, colour: Rgba) -> Option<Arc<dyn Surface>> {
+        Some(Arc::new(MetallicRoughness {
+            colour: self.colour * colour,
+            ..self.clone()
+        }))
+    }
+}
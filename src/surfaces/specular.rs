@@ -1,6 +1,14 @@
+use std::sync::Arc;
+
 use epaint::Rgba;
 
-use crate::{common_maths::maths, renderer::Ray, surfaces::Surface};
+use crate::{
+    common_maths::maths,
+    renderer::Ray,
+    sampler::Sampler,
+    scene::description::{colour_array, SpecularDescription, SurfaceDescription},
+    surfaces::Surface,
+};
 
 #[derive(Clone, Copy, Debug)]
 pub struct Specular {
@@ -12,7 +20,7 @@ impl Surface for Specular {
         return self.colour + other;
     }
 
-    fn request_rays(&self, normal_ray: &Ray, incoming_ray: &Ray) -> Vec<Ray> {
+    fn request_rays(&self, normal_ray: &Ray, incoming_ray: &Ray, _: &mut dyn Sampler) -> Vec<Ray> {
         let v = vec![maths::reflected_ray(normal_ray, incoming_ray)];
         //println!("{:?}", v);
         v
@@ -23,6 +31,22 @@ impl Surface for Specular {
         rays[0].0.colour.multiply(0.5) + self.colour.multiply(0.5)
         //self.colour
     }
+
+    fn describe(&self) -> SurfaceDescription {
+        SurfaceDescription::Specular(SpecularDescription {
+            colour: colour_array(self.colour),
+        })
+    }
+
+    fn tinted(&self, colour: Rgba) -> Option<Arc<dyn Surface>> {
+        Some(Arc::new(Specular::with_colour(self.colour * colour)))
+    }
+}
+
+impl Default for Specular {
+    fn default() -> Self {
+        Specular::new()
+    }
 }
 
 impl Specular {
@@ -0,0 +1,79 @@
+use epaint::Rgba;
+use nalgebra::Vector2;
+
+// a picture wrapped around a surface, looked up by texture coordinates from 0 to 1 that repeat
+// outside of that
+#[derive(Clone, Debug, PartialEq)]
+pub struct Texture {
+    pub width: usize,
+    pub height: usize,
+    // row by row from the top left, already in linear colour
+    pub pixels: Vec<Rgba>,
+}
+
+impl Texture {
+    // channels is how many bytes each pixel has, 1 to 4 of red, green, blue and alpha. colour
+    // pictures are stored in srgb, but ones full of numbers like roughness arent
+    pub fn from_bytes(
+        width: usize,
+        height: usize,
+        channels: usize,
+        bytes: &[u8],
+        srgb: bool,
+    ) -> Texture {
+        let pixels = bytes
+            .chunks_exact(channels)
+            .map(|pixel| {
+                let channel = |i: usize| pixel.get(i).copied().unwrap_or(pixel[0]);
+                let alpha = if channels == 4 { pixel[3] } else { 255 };
+                if srgb {
+                    Rgba::from_srgba_unmultiplied(channel(0), channel(1), channel(2), alpha)
+                } else {
+                    Rgba::from_rgba_unmultiplied(
+                        channel(0) as f32 / 255.0,
+                        channel(1) as f32 / 255.0,
+                        channel(2) as f32 / 255.0,
+                        alpha as f32 / 255.0,
+                    )
+                }
+            })
+            .collect();
+        Texture {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    // the nearest pixel, textures are usually big enough that blending them isnt worth it
+    pub fn sample(&self, uv: Vector2<f32>) -> Rgba {
+        if self.pixels.is_empty() {
+            return Rgba::WHITE;
+        }
+        let wrap = |value: f32, size: usize| {
+            let scaled = (value - value.floor()) * size as f32;
+            (scaled as usize).min(size - 1)
+        };
+        let (x, y) = (wrap(uv.x, self.width), wrap(uv.y, self.height));
+        self.pixels[y * self.width + x]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use epaint::Rgba;
+    use nalgebra::Vector2;
+
+    use super::Texture;
+
+    #[test]
+    fn textures_repeat() {
+        let texture = Texture::from_bytes(2, 1, 3, &[255, 0, 0, 0, 0, 255], false);
+        let red = Rgba::from_rgb(1.0, 0.0, 0.0);
+        let blue = Rgba::from_rgb(0.0, 0.0, 1.0);
+        assert_eq!(texture.sample(Vector2::new(0.25, 0.5)), red);
+        assert_eq!(texture.sample(Vector2::new(0.75, 0.5)), blue);
+        assert_eq!(texture.sample(Vector2::new(1.25, 0.5)), red);
+        assert_eq!(texture.sample(Vector2::new(-0.25, 0.5)), blue);
+    }
+}